@@ -0,0 +1,119 @@
+use std::hint::black_box;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rusty_systems::interpretation::abop::library;
+use rusty_systems::prelude::*;
+use rusty_systems::system::find_matching;
+use rusty_systems::system::table::ProductionTable;
+
+const PLANTS: [&str; 6] = ["fig1_24a", "fig1_24b", "fig1_24c", "fig1_24d", "fig1_24e", "fig1_24f"];
+const DEPTHS: [usize; 3] = [3, 5, 7];
+
+/// Derives the bracketed plants of fig 1.24 of ABOP.
+fn plants(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Fig 1.24");
+    group.sample_size(20);
+
+    for name in PLANTS {
+        let (interpretation, system, axiom) = library::parse(name).unwrap();
+        let settings = interpretation.run_settings();
+        group.bench_function(name, |b| b.iter(|| system.derive(black_box(axiom.clone()), settings.clone())));
+    }
+
+    group.finish();
+}
+
+/// Derives fig 1.24d of ABOP, whose productions are deterministic, to several depths.
+fn deterministic(c: &mut Criterion) {
+    let (_, system, axiom) = library::parse("fig1_24d").unwrap();
+
+    let mut group = c.benchmark_group("Deterministic");
+    group.sample_size(20);
+
+    for depth in DEPTHS {
+        group.bench_with_input(BenchmarkId::from_parameter(depth), &depth, |b, depth| {
+            b.iter(|| system.derive(black_box(axiom.clone()), RunSettings::for_max_iterations(*depth)))
+        });
+    }
+
+    group.finish();
+}
+
+/// Derives the stochastic plant of fig 1.27 of ABOP to several depths.
+fn stochastic(c: &mut Criterion) {
+    let system = System::new();
+    system.add_production("Forward -> 0.33 Forward [ + Forward ] Forward [ - Forward ] Forward").unwrap();
+    system.add_production("Forward -> 0.33 Forward [ + Forward ] Forward").unwrap();
+    system.add_production("Forward -> 0.34 Forward [ - Forward ] Forward").unwrap();
+    let axiom: ProductionString = "Forward".parse().unwrap();
+
+    let mut group = c.benchmark_group("Stochastic");
+    group.sample_size(20);
+
+    for depth in DEPTHS {
+        group.bench_with_input(BenchmarkId::from_parameter(depth), &depth, |b, depth| {
+            b.iter(|| system.derive(black_box(axiom.clone()), RunSettings::for_max_iterations(*depth)))
+        });
+    }
+
+    group.finish();
+}
+
+/// A signal that travels along a string, one symbol per generation. Every symbol is checked
+/// against a left context, and so this grows with the cost of context checks.
+fn context(c: &mut Criterion) {
+    let system = System::new();
+    system.add_production("B < A -> B").unwrap();
+    system.add_production("B -> C").unwrap();
+
+    let axiom = |length: usize| -> ProductionString {
+        std::iter::once("B").chain((0..length).map(|_| "A"))
+            .map(|name| Symbol::build(name).unwrap())
+            .collect()
+    };
+
+    let mut group = c.benchmark_group("Context sensitive");
+    group.sample_size(20);
+
+    for length in [100, 1_000, 10_000] {
+        let axiom = axiom(length);
+        group.bench_with_input(BenchmarkId::new("length", length), &axiom, |b, axiom| {
+            b.iter(|| system.derive(black_box(axiom.clone()), RunSettings::for_max_iterations(10)))
+        });
+    }
+
+    let long = axiom(1_000);
+    for depth in DEPTHS {
+        group.bench_with_input(BenchmarkId::new("depth", depth), &depth, |b, depth| {
+            b.iter(|| system.derive(black_box(long.clone()), RunSettings::for_max_iterations(*depth)))
+        });
+    }
+
+    group.finish();
+}
+
+/// Finds the production for every symbol of a derived plant, checking every production
+/// in turn, and then using a table indexed by symbol.
+fn lookup(c: &mut Criterion) {
+    let (interpretation, system, axiom) = library::parse("fig1_24d").unwrap();
+    let string = system.derive(axiom, interpretation.run_settings()).unwrap();
+
+    // Productions for symbols that the plant does not use, as a larger grammar would have.
+    let mut productions: Vec<_> = (0..50)
+        .map(|i| format!("Unused{i} -> Forward Unused{i}").parse().unwrap())
+        .collect();
+    productions.extend(["X -> Forward [ + X ] Forward [ - X ] + X", "Forward -> Forward Forward"]
+        .map(|p| p.parse().unwrap()));
+
+    let mut group = c.benchmark_group("Lookup");
+    group.bench_function("find_matching", |b| b.iter(|| {
+        (0..string.len()).filter(|i| find_matching(&productions, black_box(&string), *i).is_some()).count()
+    }));
+    group.bench_function("ProductionTable", |b| b.iter(|| {
+        let table = ProductionTable::new(&productions);
+        (0..string.len()).filter(|i| table.find(black_box(&string), *i).is_some()).count()
+    }));
+    group.finish();
+}
+
+criterion_group!(benches, plants, deterministic, stochastic, context, lookup);
+criterion_main!(benches);
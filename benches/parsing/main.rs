@@ -1,5 +1,6 @@
 use std::hint::black_box;
 use criterion::{criterion_group, criterion_main, Criterion};
+use rusty_systems::interpretation::abop::{library, parser};
 use rusty_systems::parser::{parse_prod_string, parse_production};
 
 fn criterion_benchmark(c: &mut Criterion) {
@@ -8,5 +9,24 @@ fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function("Prod rule len 4", |b| b.iter(|| parse_production(black_box("A -> B C D E"))));
 }
 
-criterion_group!(benches, criterion_benchmark);
-criterion_main!(benches);
\ No newline at end of file
+/// Parses the plant files of the figure library, and then all of them at once as a
+/// file of named systems.
+fn plants(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Plant files");
+
+    for figure in library::FIGURES {
+        group.bench_function(figure.name, |b| b.iter(|| parser::parse(black_box(figure.plant))));
+    }
+
+    let systems = library::FIGURES.iter()
+        .map(|figure| format!("system {} {{\n{}\n}}", figure.name, figure.plant))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    assert_eq!(parser::parse_systems(&systems).unwrap().len(), library::FIGURES.len());
+    group.bench_function("all figures", |b| b.iter(|| parser::parse_systems(black_box(&systems))));
+
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark, plants);
+criterion_main!(benches);
@@ -0,0 +1,40 @@
+use std::hint::black_box;
+use criterion::{criterion_group, criterion_main, Criterion};
+use rusty_systems::interpretation::abop::library;
+use rusty_systems::interpretation::abop::AbopSvgInterpretation;
+use rusty_systems::interpretation::svg::SvgElement;
+use rusty_systems::prelude::*;
+
+const PLANTS: [&str; 6] = ["fig1_24a", "fig1_24b", "fig1_24c", "fig1_24d", "fig1_24e", "fig1_24f"];
+
+/// Moves the turtle over the derived plants of fig 1.24 of ABOP.
+fn interpret(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Interpret");
+
+    for name in PLANTS {
+        let (interpretation, system, axiom) = library::parse(name).unwrap();
+        let string = system.derive(axiom, interpretation.run_settings()).unwrap();
+        group.bench_function(name, |b| b.iter(|| interpretation.interpret(&system, black_box(&string))));
+    }
+
+    group.finish();
+}
+
+/// Writes the derived plants of fig 1.24 of ABOP as SVG.
+fn svg(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Svg");
+
+    for name in PLANTS {
+        let (interpretation, system, axiom) = library::parse(name).unwrap();
+        let string = system.derive(axiom, interpretation.run_settings()).unwrap();
+        let svg = AbopSvgInterpretation::new_with(500, 500, interpretation)
+            .interpret(&system, &string)
+            .unwrap();
+        group.bench_function(name, |b| b.iter(|| black_box(&svg).to_svg()));
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, interpret, svg);
+criterion_main!(benches);
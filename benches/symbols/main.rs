@@ -0,0 +1,53 @@
+use std::hint::black_box;
+use std::thread;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rusty_systems::parser::parse_prod_string;
+
+const THREADS: [usize; 3] = [1, 2, 4];
+
+/// A string of a hundred thousand symbols, from a vocabulary of ten names.
+fn long_string() -> String {
+    let names = ["Forward", "+", "X", "[", "-", "Y", "]", "Move", "Leaf", "Stem"];
+    (0..100_000).map(|i| names[i % names.len()]).collect::<Vec<_>>().join(" ")
+}
+
+/// Parses the same string on several threads at once. Every symbol has been seen before,
+/// and so this only looks up existing codes.
+fn parsing(c: &mut Criterion) {
+    let text = long_string();
+    assert_eq!(parse_prod_string(&text).unwrap().len(), 100_000);
+
+    let mut group = c.benchmark_group("Parse 100k symbols");
+    group.sample_size(10);
+    for threads in THREADS {
+        group.bench_with_input(BenchmarkId::from_parameter(threads), &threads, |b, threads| b.iter(|| {
+            thread::scope(|scope| {
+                for _ in 0..*threads {
+                    scope.spawn(|| parse_prod_string(black_box(&text)).unwrap());
+                }
+            })
+        }));
+    }
+    group.finish();
+}
+
+/// Formats the same string on several threads at once.
+fn formatting(c: &mut Criterion) {
+    let string = parse_prod_string(&long_string()).unwrap();
+
+    let mut group = c.benchmark_group("Format 100k symbols");
+    group.sample_size(10);
+    for threads in THREADS {
+        group.bench_with_input(BenchmarkId::from_parameter(threads), &threads, |b, threads| b.iter(|| {
+            thread::scope(|scope| {
+                for _ in 0..*threads {
+                    scope.spawn(|| black_box(&string).to_string());
+                }
+            })
+        }));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parsing, formatting);
+criterion_main!(benches);
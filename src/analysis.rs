@@ -0,0 +1,295 @@
+//! Static analysis of the productions of a [`System`].
+//!
+//! As grammars grow, it becomes difficult to keep track of which productions are ever
+//! used. The [`analyse`] function (and [`System::analyse`]) inspects a set of productions,
+//! starting from an axiom, and returns a [`Report`] of:
+//!
+//! * productions that can never be applied, because their symbols are never produced from the axiom,
+//! * productions that can never be applied, because other productions always take precedence over them,
+//! * symbols that are produced, but are neither rewritten by a production nor understood by
+//!   the [`Interpretation`], and
+//! * symbols that are not declared in the system's [`SystemFamily`].
+//!
+//! ```
+//! use rusty_systems::prelude::*;
+//! use rusty_systems::interpretation::abop::AbopTurtleInterpretation;
+//!
+//! let system = AbopTurtleInterpretation::system().unwrap();
+//! system.add_production("X -> Forward [ + X ]").unwrap();
+//! system.add_production("Y -> Forward").unwrap();
+//!
+//! let axiom = "X".parse().unwrap();
+//! let report = system.analyse(&axiom, &AbopTurtleInterpretation::default()).unwrap();
+//!
+//! // Nothing produces Y
+//! assert_eq!(report.unreachable_productions.len(), 1);
+//! ```
+
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+
+use crate::prelude::*;
+use crate::productions::{Production, ProductionHead};
+use crate::symbols::iterator::SymbolIterable;
+
+/// The results of analysing a set of productions. See [`analyse`].
+///
+/// All symbols are sorted by name, and productions are in the order
+/// they were given to [`analyse`].
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    /// Productions whose target, or whose context, never occurs in any string
+    /// derived from the axiom.
+    pub unreachable_productions: Vec<Production>,
+    /// Pairs of productions, where the first production can never be applied because
+    /// the second one matches whenever it does, and always takes precedence.
+    pub shadowed_productions: Vec<(Production, Production)>,
+    /// Symbols that can occur in a derived string, but which are not rewritten by any production,
+    /// and which the interpretation does not understand.
+    pub uninterpreted_symbols: Vec<Symbol>,
+    /// Symbols used by the axiom or the productions which are not declared in the family.
+    pub undeclared_symbols: Vec<Symbol>
+}
+
+impl Report {
+    /// Returns true iff the analysis found nothing to report.
+    pub fn is_empty(&self) -> bool {
+        self.unreachable_productions.is_empty() &&
+            self.shadowed_productions.is_empty() &&
+            self.uninterpreted_symbols.is_empty() &&
+            self.undeclared_symbols.is_empty()
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No problems found");
+        }
+
+        if !self.unreachable_productions.is_empty() {
+            writeln!(f, "Unreachable productions:")?;
+            for production in &self.unreachable_productions {
+                writeln!(f, "  {}", production.head())?;
+            }
+        }
+
+        if !self.shadowed_productions.is_empty() {
+            writeln!(f, "Shadowed productions:")?;
+            for (shadowed, by) in &self.shadowed_productions {
+                writeln!(f, "  {} (shadowed by {})", shadowed.head(), by.head())?;
+            }
+        }
+
+        if !self.uninterpreted_symbols.is_empty() {
+            writeln!(f, "Symbols with no production and no interpretation:")?;
+            for symbol in &self.uninterpreted_symbols {
+                writeln!(f, "  {symbol}")?;
+            }
+        }
+
+        if !self.undeclared_symbols.is_empty() {
+            writeln!(f, "Symbols not declared in the family:")?;
+            for symbol in &self.undeclared_symbols {
+                writeln!(f, "  {symbol}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Analyses the given productions, starting from `axiom`.
+///
+/// * `family`, if given, is used to find symbols that have not been declared.
+/// * `interpreted` are the names of symbols that an [`Interpretation`] understands.
+///   See [`Interpretation::interpreted_symbols`].
+///
+/// Most of the time, you will want to use [`System::analyse`] instead.
+pub fn analyse(productions: &[Production],
+               axiom: &ProductionString,
+               family: Option<&SystemFamily>,
+               interpreted: &[String]) -> Report {
+    let reachable = reachable_symbols(productions, axiom);
+
+    let unreachable_productions = productions.iter()
+        .filter(|p| !is_head_reachable(p.head(), &reachable))
+        .cloned()
+        .collect();
+
+    let shadowed_productions = find_shadowed(productions)
+        .into_iter()
+        .map(|(shadowed, by)| (shadowed.clone(), by.clone()))
+        .collect();
+
+    let targets: HashSet<Symbol> = productions.iter()
+        .map(|p| *p.head().target())
+        .collect();
+    let interpreted: HashSet<Symbol> = interpreted.iter()
+        .filter_map(|name| Symbol::build(name).ok())
+        .collect();
+
+    let uninterpreted_symbols = sorted(reachable.iter()
+        .filter(|s| !targets.contains(s) && !interpreted.contains(s))
+        .copied());
+
+    let undeclared_symbols = match family {
+        None => Vec::new(),
+        Some(family) => {
+            let declared: HashSet<Symbol> = family.symbols()
+                .filter_map(|s| Symbol::build(&s.name).ok())
+                .collect();
+
+            let used: HashSet<Symbol> = axiom.all_symbols_iter()
+                .chain(productions.iter().flat_map(|p| p.all_symbols_iter().filter(|s| !p.head().is_pattern(*s))))
+                .collect();
+
+            sorted(used.into_iter().filter(|s| !declared.contains(s)))
+        }
+    };
+
+    Report {
+        unreachable_productions,
+        shadowed_productions,
+        uninterpreted_symbols,
+        undeclared_symbols
+    }
+}
+
+/// Returns every symbol that may occur in a string derived from `axiom`, including
+/// the axiom's own symbols.
+///
+/// A production contributes its bodies' symbols once its target and all of its context
+/// symbols, or symbols matching its context patterns, are reachable.
+pub fn reachable_symbols(productions: &[Production], axiom: &ProductionString) -> HashSet<Symbol> {
+    let mut reachable: HashSet<Symbol> = axiom.all_symbols();
+    let mut used = vec![false; productions.len()];
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+
+        for (i, production) in productions.iter().enumerate() {
+            if used[i] || !is_head_reachable(production.head(), &reachable) {
+                continue;
+            }
+
+            used[i] = true;
+            changed = true;
+            production.all_bodies()
+                .iter()
+                .flat_map(|b| b.all_symbols_iter())
+                .for_each(|s| { reachable.insert(s); });
+        }
+    }
+
+    reachable
+}
+
+/// A head can match once its target, and something matching each of its contexts' symbols
+/// and patterns, are reachable.
+fn is_head_reachable(head: &ProductionHead, reachable: &HashSet<Symbol>) -> bool {
+    head.all_symbols_iter().all(|symbol| match head.is_pattern(symbol) {
+        true => reachable.iter().any(|s| head.context_matches(symbol, *s)),
+        false => reachable.contains(&symbol)
+    })
+}
+
+/// Returns pairs of productions where the first can never be applied, because the
+/// second matches every position the first does, and takes precedence over it when used by
+/// [`find_matching`](crate::system::find_matching).
+pub fn find_shadowed(productions: &[Production]) -> Vec<(&Production, &Production)> {
+    let mut result = Vec::new();
+
+    for (i, production) in productions.iter().enumerate() {
+        let shadow = productions.iter()
+            .enumerate()
+            .filter(|(j, other)| {
+                *j != i && other.head().subsumes(production.head()) && match other.precedence_cmp(production) {
+                    Ordering::Greater => true,
+                    Ordering::Equal => *j < i,
+                    Ordering::Less => false
+                }
+            })
+            .map(|(_, other)| other)
+            .next();
+
+        if let Some(shadow) = shadow {
+            result.push((production, shadow));
+        }
+    }
+
+    result
+}
+
+fn sorted<I: Iterator<Item=Symbol>>(symbols: I) -> Vec<Symbol> {
+    let mut result: Vec<Symbol> = symbols.collect();
+    result.sort_by_key(|s| s.to_string());
+    result
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpretation::abop::AbopTurtleInterpretation;
+    use crate::parser::parse_prod_string;
+
+    #[test]
+    fn finds_unreachable_productions() {
+        let system = System::new();
+        system.add_production("A -> B").unwrap();
+        system.add_production("B -> A C").unwrap();
+        system.add_production("D -> A").unwrap();
+        system.add_production("E < A -> A").unwrap();
+
+        let axiom = parse_prod_string("A").unwrap();
+        let report = system.analyse(&axiom, &AbopTurtleInterpretation::default()).unwrap();
+
+        let heads: Vec<_> = report.unreachable_productions.iter()
+            .map(|p| p.head().to_string())
+            .collect();
+        assert_eq!(heads, ["D", "E < A"]);
+    }
+
+    #[test]
+    fn finds_uninterpreted_symbols() {
+        let system = AbopTurtleInterpretation::system().unwrap();
+        system.add_production("X -> Forward [ + X Leaf ] Apex").unwrap();
+
+        let axiom = parse_prod_string("X").unwrap();
+        let report = system.analyse(&axiom, &AbopTurtleInterpretation::default()).unwrap();
+
+        let names: Vec<_> = report.uninterpreted_symbols.iter().map(|s| s.to_string()).collect();
+        assert_eq!(names, ["Apex", "Leaf"]);
+
+        let names: Vec<_> = report.undeclared_symbols.iter().map(|s| s.to_string()).collect();
+        assert_eq!(names, ["Apex", "Leaf"]);
+    }
+
+    #[test]
+    fn finds_shadowed_productions() {
+        let productions = vec![
+            "A < X -> F".parse::<Production>().unwrap(),
+            "X -> G".parse::<Production>().unwrap().with_priority(1),
+            "Y -> G".parse::<Production>().unwrap(),
+        ];
+
+        let shadowed = find_shadowed(&productions);
+        assert_eq!(shadowed.len(), 1);
+        assert_eq!(shadowed[0].0.head().to_string(), "A < X");
+        assert_eq!(shadowed[0].1.head().to_string(), "X");
+    }
+
+    #[test]
+    fn clean_system_has_empty_report() {
+        let system = AbopTurtleInterpretation::system().unwrap();
+        system.add_production("X -> Forward [ + X ] - X").unwrap();
+        system.add_production("Forward -> Forward Forward").unwrap();
+
+        let axiom = parse_prod_string("X").unwrap();
+        let report = system.analyse(&axiom, &AbopTurtleInterpretation::default()).unwrap();
+        assert!(report.is_empty(), "{report}");
+    }
+}
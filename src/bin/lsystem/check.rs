@@ -0,0 +1,42 @@
+use std::process::ExitCode;
+
+use crate::{error_style, green, heading_style, read_plant_file};
+use crate::cli::{CheckArgs, Cli};
+
+pub fn check(args: &Cli, check: &CheckArgs) -> ExitCode {
+    if args.verbose {
+        print!("Reading {} ", check.file.to_str().unwrap());
+    }
+
+    let (interpretation, system, axiom) = match read_plant_file(check.file.as_ref(), check.system.as_deref()) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            if args.verbose { println!("❌") }
+            eprint!("\n{}: ", error_style().paint("Error"));
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if args.verbose {
+        println!("{}", green().paint("\t✔"));
+    }
+
+    let report = match system.analyse(&axiom, &interpretation) {
+        Ok(report) => report,
+        Err(e) => {
+            eprint!("\n{}: ", error_style().paint("Error"));
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if report.is_empty() {
+        println!("{}", green().paint("No problems found"));
+        return ExitCode::SUCCESS;
+    }
+
+    println!("{}", heading_style().paint("Report:"));
+    print!("{}", report);
+    ExitCode::FAILURE
+}
@@ -1,4 +1,5 @@
-use clap::{Args, Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use rusty_systems::interpretation::abop::BranchPolicy;
 
 #[derive(Parser, Debug)]
 #[command(version, long_about = None)]
@@ -18,7 +19,14 @@ pub struct Cli {
 #[derive(Debug, Args)]
 pub struct InterpretationArgs {
     /// The input file, eg: a plant file
-    pub file: Box<std::path::Path>, 
+    #[arg(required_unless_present = "builtin")]
+    pub file: Option<Box<std::path::Path>>,
+    /// The name of the system to use, for files containing several named systems
+    #[arg(long)]
+    pub system: Option<String>,
+    /// Interpret a figure from the Algorithmic Beauty of Plants, such as fig1_24d, instead of a file
+    #[arg(long, conflicts_with_all = ["file", "system"])]
+    pub builtin: Option<String>,
     /// Where the SVG file should be saved 
     #[arg(short, long, default_value = "out.svg")]
     pub output: Box<std::path::Path>,
@@ -27,7 +35,82 @@ pub struct InterpretationArgs {
     pub width: usize,
     #[arg(long, default_value = "500")]
     /// The image height
-    pub height: usize
+    pub height: usize,
+    /// Refuse to derive strings longer than this
+    #[arg(long)]
+    pub max_length: Option<usize>,
+    /// How to handle branches that are closed without being opened, or are never closed
+    #[arg(long, value_enum, default_value = "error")]
+    pub branches: Branches
+}
+
+#[derive(Debug, Copy, Clone, ValueEnum)]
+pub enum Branches {
+    /// Unbalanced branches are an error
+    Error,
+    /// Ignore symbols closing branches that were never opened
+    IgnoreExtraPops,
+    /// Close branches that were never closed
+    AutoClose,
+    /// Both ignore extra closing symbols and close unclosed branches
+    Lenient
+}
+
+impl From<Branches> for BranchPolicy {
+    fn from(value: Branches) -> Self {
+        match value {
+            Branches::Error => BranchPolicy::Error,
+            Branches::IgnoreExtraPops => BranchPolicy::IgnoreExtraPops,
+            Branches::AutoClose => BranchPolicy::AutoClose,
+            Branches::Lenient => BranchPolicy::Lenient
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct DescribeArgs {
+    /// The name of the family to describe. Describes every registered family if not given
+    pub name: Option<String>,
+    /// A file defining a family of symbols, which is registered and then described
+    #[arg(long)]
+    pub family: Option<Box<std::path::Path>>,
+    /// Only list the names of the families
+    #[arg(long)]
+    pub list: bool,
+    /// How the description is printed
+    #[arg(long, value_enum, default_value = "text")]
+    pub format: Format
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// Aligned text for reading in a terminal
+    Text,
+    /// A JSON array of families
+    Json,
+    /// Markdown headings and tables
+    Markdown
+}
+
+#[derive(Debug, Args)]
+pub struct CheckArgs {
+    /// The input file, eg: a plant file
+    pub file: Box<std::path::Path>,
+    /// The name of the system to check, for files containing several named systems
+    #[arg(long)]
+    pub system: Option<String>
+}
+
+#[derive(Debug, Args)]
+pub struct StatsArgs {
+    /// The input file, eg: a plant file
+    pub file: Box<std::path::Path>,
+    /// The name of the system to use, for files containing several named systems
+    #[arg(long)]
+    pub system: Option<String>,
+    /// Print comma separated values instead of a table
+    #[arg(long)]
+    pub csv: bool
 }
 
 #[derive(Debug, Subcommand)]
@@ -35,5 +118,9 @@ pub enum Command {
     /// Derive and interpret an SVG of a production string
     Interpret(InterpretationArgs),
     /// Describe the tokens available for use
-    Describe,
+    Describe(DescribeArgs),
+    /// Report unused productions and symbols in a plant file
+    Check(CheckArgs),
+    /// Print symbol counts for each generation of a plant file's derivation
+    Stats(StatsArgs),
 }
\ No newline at end of file
@@ -1,19 +1,30 @@
 use std::process::ExitCode;
 
 use rusty_systems::prelude::*;
-use rusty_systems::interpretation::abop::parser;
+use rusty_systems::interpretation::abop::library;
 use rusty_systems::interpretation::svg::SvgPathInterpretation;
 
-use crate::{error_style, green};
+use crate::{error_style, green, read_plant_file, warning_style};
 use crate::cli::{Cli, InterpretationArgs};
 
 pub fn handle_derive(args: &Cli, derive: &InterpretationArgs) -> ExitCode {
-    if args.verbose {
-        print!("Reading {} ", derive.file.to_str().unwrap());
-    }
+    let result = match (&derive.builtin, &derive.file) {
+        (Some(name), _) => {
+            if args.verbose {
+                print!("Reading figure {} ", name);
+            }
+            library::parse(name)
+        }
+        (None, Some(file)) => {
+            if args.verbose {
+                print!("Reading {} ", file.to_str().unwrap());
+            }
+            read_plant_file(file.as_ref(), derive.system.as_deref())
+        }
+        (None, None) => unreachable!("clap requires a file or a builtin figure")
+    };
 
     let (interpretation, system, axiom) = {
-        let result = parser::parse_file(derive.file.as_ref());
         if let Err(e) = result {
             if args.verbose { println!("❌") }
             eprint!("\n{}: ", error_style().paint("Error"));
@@ -28,9 +39,21 @@ pub fn handle_derive(args: &Cli, derive: &InterpretationArgs) -> ExitCode {
         println!("{}", green().paint("\t✔"));
     }
 
+    for (first, second) in system.ambiguities().unwrap_or_default() {
+        eprintln!("{}: productions [{}] and [{}] are ambiguous; [{}] will be used. \
+                   Give one a priority after its arrow, such as `X ->(1) F`, to choose between them",
+                  warning_style().paint("Warning"), first, second, first);
+    }
+
+    let interpretation = interpretation.with_branch_policy(derive.branches.into());
     let interpretation = SvgPathInterpretation::new_with(derive.width, derive.height, interpretation);
 
-    let result = system.derive(axiom, interpretation.run_settings());
+    let settings = RunSettings {
+        max_length: derive.max_length,
+        ..interpretation.run_settings()
+    };
+
+    let result = system.derive(axiom, settings);
     if let Err(e) = result {
         eprintln!("\n{}", error_style().paint("Error"));
         eprintln!("{}", e);
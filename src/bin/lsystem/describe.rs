@@ -1,23 +1,179 @@
 use std::process::ExitCode;
-use rusty_systems::interpretation::abop;
-use rusty_systems::system::family::SymbolDescription;
-use crate::heading_style;
+use std::sync::Arc;
+use rusty_systems::system::family::{family_names, get_family, parser, SymbolDescription, SystemFamily};
+use crate::{error_style, green, heading_style};
+use crate::cli::{Cli, DescribeArgs, Format};
 
 
-pub fn describe() -> ExitCode {
-    let family = abop::abop_family();
+pub fn describe(args: &Cli, describe: &DescribeArgs) -> ExitCode {
+    let mut loaded: Option<String> = None;
+    if let Some(file) = &describe.family {
+        if args.verbose {
+            print!("Reading {} ", file.to_str().unwrap());
+        }
 
+        match parser::load_file(file.as_ref()) {
+            Ok(family) => {
+                if args.verbose { println!("{}", green().paint("\t✔")) }
+                loaded = Some(family.name().clone());
+            }
+            Err(e) => {
+                if args.verbose { println!("❌") }
+                eprint!("\n{}: ", error_style().paint("Error"));
+                eprintln!("{}", e);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    let names = match describe.name.as_ref().or(loaded.as_ref()) {
+        Some(name) => vec![name.clone()],
+        None => family_names()
+    };
+
+    let mut families: Vec<Arc<SystemFamily>> = Vec::with_capacity(names.len());
+    for name in names {
+        match get_family(&name) {
+            Some(family) => families.push(family),
+            None => {
+                eprintln!("{}: there is no family called [{}]", error_style().paint("Error"), name);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    match (describe.format, describe.list) {
+        (Format::Text, true) => families.iter().for_each(|f| println!("{}", f.name())),
+        (Format::Json, true) => {
+            let names: Vec<String> = families.iter().map(|f| json_string(f.name())).collect();
+            println!("[{}]", names.join(", "));
+        }
+        (Format::Markdown, true) => families.iter().for_each(|f| println!("* {}", f.name())),
+        (Format::Text, false) => {
+            for (i, family) in families.iter().enumerate() {
+                if i > 0 { println!() }
+                print_text(family);
+            }
+        }
+        (Format::Json, false) => {
+            let families: Vec<String> = families.iter().map(|f| to_json(f)).collect();
+            println!("[\n{}\n]", families.join(",\n"));
+        }
+        (Format::Markdown, false) => {
+            for (i, family) in families.iter().enumerate() {
+                if i > 0 { println!() }
+                print_markdown(family);
+            }
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn print_text(family: &SystemFamily) {
+    let has_actions = family.symbols().any(|t| t.action.is_some());
     let print= |t : &SymbolDescription| {
         print!("  {: <10}", t.name);
+        if has_actions {
+            print!("{: <10}", t.action.as_ref().map(|a| format!("[{}]", a)).unwrap_or_default());
+        }
         t.description.iter().for_each(|d| print!("{}", d));
         println!();
     };
 
-    println!("{}", heading_style().paint("Terminals:"));
+    println!("{} {}", heading_style().paint("Family:"), family.name());
+    if !family.parents().is_empty() {
+        println!("{} {}", heading_style().paint("Extends:"), family.parents().join(", "));
+    }
+
+    println!("\n{}", heading_style().paint("Terminals:"));
     family.terminals().for_each(&print);
 
     println!("\n{}", heading_style().paint("Productions:"));
     family.productions().for_each(&print);
 
-    ExitCode::SUCCESS
+    if family.classes().next().is_some() {
+        println!("\n{}", heading_style().paint("Classes:"));
+        family.classes().for_each(|(name, members)| println!("  {: <10}{}", name, members.join(" ")));
+    }
+}
+
+fn print_markdown(family: &SystemFamily) {
+    println!("## {}", markdown_cell(family.name()));
+    if !family.parents().is_empty() {
+        println!("\nExtends {}.", family.parents().join(", "));
+    }
+
+    let table = |heading: &str, symbols: Vec<&SymbolDescription>| {
+        println!("\n### {}\n", heading);
+        println!("| Symbol | Action | Description |");
+        println!("|--------|--------|-------------|");
+        for symbol in symbols {
+            println!("| `{}` | {} | {} |",
+                     markdown_cell(&symbol.name),
+                     symbol.action.as_deref().map(markdown_cell).unwrap_or_default(),
+                     symbol.description.as_deref().map(markdown_cell).unwrap_or_default());
+        }
+    };
+
+    table("Terminals", family.terminals().collect());
+    table("Productions", family.productions().collect());
+
+    if family.classes().next().is_some() {
+        println!("\n### Classes\n");
+        println!("| Class | Members |");
+        println!("|-------|---------|");
+        for (name, members) in family.classes() {
+            println!("| `{}` | {} |", markdown_cell(name), markdown_cell(&members.join(" ")));
+        }
+    }
+}
+
+fn to_json(family: &SystemFamily) -> String {
+    let symbols = |symbols: Vec<&SymbolDescription>| {
+        let symbols: Vec<String> = symbols.into_iter()
+            .map(|s| format!("{{\"name\": {}, \"action\": {}, \"description\": {}}}",
+                             json_string(&s.name),
+                             s.action.as_deref().map(json_string).unwrap_or_else(|| String::from("null")),
+                             s.description.as_deref().map(json_string).unwrap_or_else(|| String::from("null"))))
+            .collect();
+        format!("[{}]", symbols.join(", "))
+    };
+
+    let list = |items: &[String]| {
+        let items: Vec<String> = items.iter().map(|i| json_string(i)).collect();
+        format!("[{}]", items.join(", "))
+    };
+
+    let classes: Vec<String> = family.classes()
+        .map(|(name, members)| format!("{{\"name\": {}, \"members\": {}}}", json_string(name), list(members)))
+        .collect();
+
+    format!("  {{\"name\": {}, \"parents\": {}, \"terminals\": {}, \"productions\": {}, \"classes\": [{}]}}",
+            json_string(family.name()),
+            list(family.parents()),
+            symbols(family.terminals().collect()),
+            symbols(family.productions().collect()),
+            classes.join(", "))
+}
+
+fn json_string(value: &str) -> String {
+    let mut result = String::with_capacity(value.len() + 2);
+    result.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\t' => result.push_str("\\t"),
+            c if c.is_control() => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c)
+        }
+    }
+    result.push('"');
+    result
+}
+
+fn markdown_cell(value: &str) -> String {
+    value.replace('|', "\\|")
 }
@@ -3,10 +3,13 @@ use std::sync::OnceLock;
 
 use ansi_term::{Color, Style};
 use clap::Parser;
+use rusty_systems::interpretation::abop::{self, parser::{self, ParsedAbop}};
 
 use crate::derive::handle_derive;
 
 mod derive;
+mod check;
+mod stats;
 mod describe;
 mod cli;
 
@@ -14,14 +17,20 @@ mod cli;
 
 fn main() -> ExitCode {
     let args = cli::Cli::parse();
-
+    abop::register_family();
 
     match &args.command {
         cli::Command::Interpret(derive) => {
             handle_derive(&args, derive)
         },
-        cli::Command::Describe => {
-            describe::describe()
+        cli::Command::Describe(describe) => {
+            describe::describe(&args, describe)
+        },
+        cli::Command::Check(check) => {
+            check::check(&args, check)
+        },
+        cli::Command::Stats(stats) => {
+            stats::stats(&args, stats)
         }
     }
 }
@@ -29,6 +38,14 @@ fn main() -> ExitCode {
 
 
 
+/// Reads a plant file, choosing the named system if one is given.
+fn read_plant_file(file: &std::path::Path, system: Option<&str>) -> rusty_systems::Result<ParsedAbop> {
+    match system {
+        Some(name) => parser::parse_system_file(file, name),
+        None => parser::parse_file(file)
+    }
+}
+
 fn green() -> &'static Style {
     static GREEN: OnceLock<Style> = OnceLock::new();
     GREEN.get_or_init(|| Style::new().fg(Color::Green))
@@ -39,6 +56,11 @@ fn error_style() -> &'static Style {
     ERROR.get_or_init(|| Color::Red.underline())
 }
 
+fn warning_style() -> &'static Style {
+    static WARNING: OnceLock<Style> = OnceLock::new();
+    WARNING.get_or_init(|| Style::new().fg(Color::Yellow))
+}
+
 fn heading_style() -> &'static Style {
     static HEADING: OnceLock<Style> = OnceLock::new();
     HEADING.get_or_init(|| Color::White.underline().bold())
@@ -0,0 +1,55 @@
+use std::process::ExitCode;
+
+use rusty_systems::prelude::*;
+use rusty_systems::strings::statistics::Statistics;
+
+use crate::{error_style, green, read_plant_file};
+use crate::cli::{Cli, StatsArgs};
+
+pub fn stats(args: &Cli, stats: &StatsArgs) -> ExitCode {
+    if args.verbose {
+        eprint!("Reading {} ", stats.file.to_str().unwrap());
+    }
+
+    let (interpretation, system, axiom) = match read_plant_file(stats.file.as_ref(), stats.system.as_deref()) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            if args.verbose { eprintln!("❌") }
+            eprint!("\n{}: ", error_style().paint("Error"));
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if args.verbose {
+        eprintln!("{}", green().paint("\t✔"));
+    }
+
+    let (push, pop) = match (Symbol::build("["), Symbol::build("]")) {
+        (Ok(push), Ok(pop)) => (push, pop),
+        (Err(e), _) | (_, Err(e)) => {
+            eprint!("\n{}: ", error_style().paint("Error"));
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut statistics = Statistics::new(push, pop);
+    let result = system.derive_with(axiom, interpretation.run_settings(), |generation, string| {
+        statistics.record(generation, string);
+    });
+
+    if let Err(e) = result {
+        eprint!("\n{}: ", error_style().paint("Error"));
+        eprintln!("{}", e);
+        return ExitCode::FAILURE;
+    }
+
+    if stats.csv {
+        print!("{}", statistics.to_csv());
+    } else {
+        print!("{}", statistics);
+    }
+
+    ExitCode::SUCCESS
+}
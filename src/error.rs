@@ -21,7 +21,10 @@ pub enum ErrorKind {
     /// An IO Error. 
     Io,
     /// Represents an error that cannot happen, as with [`Infallible`]
-    Infallible
+    Infallible,
+    /// The branches of a string are not balanced: a branch was closed without being
+    /// opened, or was opened and never closed. See [`Error::index`] for where this occurred.
+    Branching
 }
 
 
@@ -31,6 +34,7 @@ pub enum ErrorKind {
 pub struct Error {
     kind: ErrorKind,
     message: String,
+    index: Option<usize>,
     source: Option<Rc<dyn std::error::Error>>
 }
 
@@ -39,6 +43,7 @@ impl Default for Error {
         Self {
             kind: ErrorKind::General,
             message: String::from("An unspecified error occurred"),
+            index: None,
             source: None
         }
     }
@@ -57,7 +62,7 @@ impl Display for Error {
 
 impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
-        self.source.as_ref().map(|e| &**e)
+        self.source.as_deref()
     }
 }
 
@@ -86,6 +91,36 @@ impl Error {
     pub fn execution<T : ToString>(message: T) -> Self {
         Self::new(ErrorKind::Execution, message)
     }
+
+    /// An error about unbalanced branches, at the given symbol index of a string.
+    pub fn branching<T : ToString>(index: usize, message: T) -> Self {
+        Self::new(ErrorKind::Branching, message).with_index(index)
+    }
+
+    /// Records the index of the symbol in a [`ProductionString`](crate::prelude::ProductionString)
+    /// that caused this error.
+    pub fn with_index(mut self, index: usize) -> Self {
+        self.index = Some(index);
+        self
+    }
+
+    /// Returns this error with its message prefixed by where the error happened, such as
+    /// the name of the system being parsed.
+    pub(crate) fn with_context<S: Display>(mut self, context: S) -> Self {
+        self.message = format!("{context}: {}", self.message);
+        self
+    }
+
+    /// The kind of error this is.
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    /// The index of the symbol in a [`ProductionString`](crate::prelude::ProductionString)
+    /// that caused this error, if there is one.
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
 }
 
 impl<T> From<PoisonError<T>> for Error {
@@ -99,6 +134,7 @@ impl From<std::io::Error> for Error {
         Error {
             kind: ErrorKind::Io,
             message: error.to_string(),
+            index: None,
             source: Some(Rc::new(error)),
         }
     }
@@ -109,6 +145,7 @@ impl From<std::num::ParseIntError> for Error {
         Error {
             kind: ErrorKind::Parse,
             message: error.to_string(),
+            index: None,
             source: Some(Rc::new(error)),
         }
     }
@@ -119,6 +156,7 @@ impl From<std::num::ParseFloatError> for Error {
         Error {
             kind: ErrorKind::Parse,
             message: error.to_string(),
+            index: None,
             source: Some(Rc::new(error)),
         }
     }
@@ -22,7 +22,7 @@
 
 use std::fmt::{Display, Formatter};
 use std::iter::FromIterator;
-use std::ops::{Add, Div, Index, Neg, Sub};
+use std::ops::{Add, Div, Index, Mul, Neg, Sub};
 use std::slice::Iter;
 use std::vec::IntoIter;
 
@@ -169,6 +169,14 @@ impl Sub for Vector {
     }
 }
 
+impl Mul<f64> for Vector {
+    type Output = Vector;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Vector::new(self.x() * rhs, self.y() * rhs)
+    }
+}
+
 impl Neg for Vector {
     type Output = Vector;
 
@@ -239,15 +247,29 @@ impl Display for Vector {
 
 /// A path is a sequence of points. These can represent
 /// a line.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Path {
-    points: Vec<Point>
+    points: Vec<Point>,
+    width: Option<f64>
 }
 
 impl Path {
     #[inline]
     pub fn new() -> Self {
-        Path { points: Vec::new() }
+        Path { points: Vec::new(), width: None }
+    }
+
+    /// Returns this path drawn with the given line width.
+    #[inline]
+    pub fn with_width(mut self, width: f64) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// The width of the line that the path should be drawn with, if it has been given one.
+    #[inline]
+    pub fn width(&self) -> Option<f64> {
+        self.width
     }
 
     #[inline]
@@ -308,7 +330,7 @@ impl IntoIterator for Path {
 /// Create a [`Path`] from a collection of [`Point`] objects.
 impl FromIterator<Point> for Path {
     fn from_iter<T: IntoIterator<Item=Point>>(iter: T) -> Self {
-        Path { points: iter.into_iter().collect() }
+        Path { points: iter.into_iter().collect(), width: None }
     }
 }
 
@@ -317,7 +339,10 @@ impl Add<Vector> for Path {
     type Output = Path;
 
     fn add(self, rhs: Vector) -> Self::Output {
-        self.into_iter().map(|p| p + rhs).collect()
+        Path {
+            points: self.points.into_iter().map(|p| p + rhs).collect(),
+            width: self.width
+        }
     }
 }
 
@@ -349,6 +374,12 @@ impl BoundingBox {
         Point::new((self.max_x + self.min_x) / 2.0, (self.max_y + self.min_y) / 2.0)
     }
 
+    /// Returns true iff the point is inside of the box, or on its edges.
+    #[inline]
+    pub fn contains(&self, point: Point) -> bool {
+        (self.min_x..=self.max_x).contains(&point.x) && (self.min_y..=self.max_y).contains(&point.y)
+    }
+
     /// Returns a bounding box set up to have its values updated.
     fn initial_infinite() -> Self {
         BoundingBox {
@@ -378,7 +409,6 @@ pub trait Bounds {
 
 impl Bounds for Path {
     /// Returns the bounds for the path.
-
     fn bounds(&self) -> Option<BoundingBox> {
         let mut bounds = BoundingBox::initial_infinite();
 
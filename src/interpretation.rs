@@ -1,9 +1,10 @@
 use std::fmt::Debug;
-use crate::prelude::{ProductionString, RunSettings, System};
+use crate::prelude::{ProductionString, RunSettings, Symbol, System};
 use crate::symbols::SymbolStore;
 
 pub mod abop;
 pub mod svg;
+pub mod text;
 
 pub trait Interpretation: Debug + Sync + Send + Default {
     type Item;
@@ -20,6 +21,18 @@ pub trait Interpretation: Debug + Sync + Send + Default {
                                  symbols: &S,
                                  string: &ProductionString) -> crate::Result<Self::Item>;
 
+    /// Interprets a string given as an iterator of its symbols, such as a
+    /// [streaming derivation](crate::system::streaming).
+    ///
+    /// By default, the symbols are collected into a [`ProductionString`] and given to
+    /// [`Interpretation::interpret`]. Interpretations that can consume the symbols one at a time,
+    /// without holding the whole string in memory, should override this.
+    fn interpret_symbols<S, I>(&self, symbols: &S, string: I) -> crate::Result<Self::Item>
+        where S: SymbolStore,
+              I: IntoIterator<Item=Symbol>
+    {
+        self.interpret(symbols, &string.into_iter().collect())
+    }
 
     fn default_interpret<S: SymbolStore>(symbols: &S,
                                          string: &ProductionString) -> crate::Result<Self::Item> {
@@ -27,6 +40,14 @@ pub trait Interpretation: Debug + Sync + Send + Default {
         instance.interpret(symbols, string)
     }
 
+    /// Returns the names of the symbols that this interpretation gives a meaning to.
+    ///
+    /// This is used by [`crate::analysis`] to find symbols that are never rewritten and
+    /// also never interpreted. By default, this is empty.
+    fn interpreted_symbols(&self) -> Vec<String> {
+        Vec::new()
+    }
+
     /// Returns default run settings for this interpretation.
     ///
     /// This defines how a system should be derived.
@@ -2,24 +2,64 @@
 //!
 //! TODO Add more information.
 
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use crate::error::{Error, ErrorKind};
 use crate::geometry::{Path, Point, Vector};
 use crate::prelude::*;
-use crate::system::family::get_or_init_family;
+use crate::system::family::{self, get_or_init_family};
 use crate::interpretation::Interpretation;
 use crate::interpretation::svg::SvgPathInterpretation;
 use crate::symbols::SymbolStore;
+use crate::system::environment::{Query, QueryLocator};
+use crate::system::timed::TimedString;
 
+pub mod library;
 pub mod parser;
 
+/// The name of the query symbol, whose position is reported to an [`Environment`](crate::system::environment::Environment).
+pub const QUERY: &str = "?P";
+
+/// Makes the [`abop_family`] available from the [family registry](crate::system::family) as `ABOP`,
+/// so that it can be found by name, and extended by other families. It is built the first time
+/// that it is asked for. See [`register_builtin`](crate::system::family::register_builtin).
+///
+/// ```
+/// use rusty_systems::system::family::get_family;
+/// use rusty_systems::interpretation::abop;
+///
+/// abop::register_family();
+/// assert!(get_family("ABOP").unwrap().is_production("Forward"));
+/// ```
+pub fn register_family() {
+    family::register_builtin("ABOP", abop_family);
+}
+
 pub fn abop_family() -> SystemFamily {
     SystemFamily::define()
         .with_terminal("[", Some("Start a branch"))
         .with_terminal("]", Some("Finish a branch"))
         .with_terminal("+", Some("Turn turtle right"))
         .with_terminal("-", Some("Turn turtle left"))
+        .with_terminal("%", Some("Cut off the remainder of the branch"))
+        .with_terminal(QUERY, Some("Query the turtle's position and heading"))
+        .with_terminal("!", Some("Scale the width of the line"))
+        .with_terminal("\"", Some("Scale the length of the turtle's steps"))
+        .with_terminal("@", Some("Scale the angle that the turtle turns by"))
         .with_production("Forward", Some("Move the turtle forward, drawing a line"))
         .with_production("Move", Some("Move the turtle forward WITHOUT drawing"))
         .with_production("X", Some("A growth point for the plant / branch"))
+        .with_action("Forward", "forward")
+        .with_action("Move", "move")
+        .with_action("-", "left")
+        .with_action("+", "right")
+        .with_action("[", "push")
+        .with_action("]", "pop")
+        .with_action("!", "scale-width")
+        .with_action("\"", "scale-length")
+        .with_action("@", "scale-angle")
         .build("ABOP")
         .unwrap()
 }
@@ -39,7 +79,136 @@ pub struct AbopTurtleInterpretation {
     /// the number of iterations
     n: usize,
     /// in degrees
-    delta: f32
+    delta: f32,
+    /// in degrees, anticlockwise from the x-axis
+    heading: f64,
+    step: f64,
+    width: Option<f64>,
+    width_scale: f64,
+    angle_scale: f64,
+    length_scale: f64,
+    branch_policy: BranchPolicy,
+    actions: HashMap<Symbol, TurtleAction>
+}
+
+/// What the turtle does when it reaches a symbol. By default, the turtle performs these
+/// for the symbols of the [`abop_family`], but other symbols can be given actions using
+/// [`AbopTurtleInterpretation::with_action`] or [`AbopTurtleInterpretation::with_family_actions`].
+///
+/// Actions are named in [family definitions](crate::system::family::parser) as
+/// `forward`, `move`, `left`, `right`, `push`, `pop`, `scale-width`, `scale-length` and `scale-angle`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum TurtleAction {
+    /// Moves the turtle forward, drawing a line. This is done for `Forward`.
+    Forward,
+    /// Moves the turtle forward without drawing. This is done for `Move`.
+    Move,
+    /// Turns the turtle left. This is done for `-`.
+    TurnLeft,
+    /// Turns the turtle right. This is done for `+`.
+    TurnRight,
+    /// Starts a branch. This is done for `[`.
+    Push,
+    /// Ends a branch, returning the turtle to where the branch started. This is done for `]`.
+    Pop,
+    /// Multiplies the width of the line by [`AbopTurtleInterpretation::width_scale`]. This is done for `!`.
+    ScaleWidth,
+    /// Multiplies the length of the turtle's steps by [`AbopTurtleInterpretation::length_scale`].
+    /// This is done for `"`.
+    ScaleLength,
+    /// Multiplies the angle that the turtle turns by by [`AbopTurtleInterpretation::angle_scale`].
+    /// This is done for `@`, since `;` ends productions.
+    ScaleAngle
+}
+
+impl TurtleAction {
+    /// The symbols of the [`abop_family`] that the turtle acts on, along with their actions.
+    pub const DEFAULTS: [(&'static str, TurtleAction); 9] = [
+        ("Forward", TurtleAction::Forward),
+        ("Move", TurtleAction::Move),
+        ("-", TurtleAction::TurnLeft),
+        ("+", TurtleAction::TurnRight),
+        ("[", TurtleAction::Push),
+        ("]", TurtleAction::Pop),
+        ("!", TurtleAction::ScaleWidth),
+        ("\"", TurtleAction::ScaleLength),
+        ("@", TurtleAction::ScaleAngle)
+    ];
+
+    /// Whether the default symbol for this action must be registered for the turtle to
+    /// interpret strings. The scaling actions are optional.
+    #[inline]
+    fn is_required(self) -> bool {
+        !matches!(self, TurtleAction::ScaleWidth | TurtleAction::ScaleLength | TurtleAction::ScaleAngle)
+    }
+}
+
+impl Display for TurtleAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            TurtleAction::Forward => "forward",
+            TurtleAction::Move => "move",
+            TurtleAction::TurnLeft => "left",
+            TurtleAction::TurnRight => "right",
+            TurtleAction::Push => "push",
+            TurtleAction::Pop => "pop",
+            TurtleAction::ScaleWidth => "scale-width",
+            TurtleAction::ScaleLength => "scale-length",
+            TurtleAction::ScaleAngle => "scale-angle"
+        })
+    }
+}
+
+impl FromStr for TurtleAction {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "forward" => Ok(TurtleAction::Forward),
+            "move" => Ok(TurtleAction::Move),
+            "left" => Ok(TurtleAction::TurnLeft),
+            "right" => Ok(TurtleAction::TurnRight),
+            "push" => Ok(TurtleAction::Push),
+            "pop" => Ok(TurtleAction::Pop),
+            "scale-width" => Ok(TurtleAction::ScaleWidth),
+            "scale-length" => Ok(TurtleAction::ScaleLength),
+            "scale-angle" => Ok(TurtleAction::ScaleAngle),
+            other => Err(Error::new(ErrorKind::Parse, format!("[{other}] is not a turtle action")))
+        }
+    }
+}
+
+/// Determines how [`AbopTurtleInterpretation`] handles strings whose branches are
+/// not balanced.
+///
+/// Unbalanced branches are common while editing a grammar, or after branches have been pruned.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum BranchPolicy {
+    /// Unbalanced branches are an [`ErrorKind::Branching`](crate::error::ErrorKind::Branching) error,
+    /// which records the index of the offending symbol.
+    #[default]
+    Error,
+    /// Symbols that close a branch that was never opened are ignored. Branches that are
+    /// never closed are an error.
+    IgnoreExtraPops,
+    /// Branches that are never closed are closed at the end of the string. Symbols that close a branch that
+    /// was never opened are an error.
+    AutoClose,
+    /// Both ignores symbols that close a branch that was never opened, and closes branches
+    /// that were never closed.
+    Lenient
+}
+
+impl BranchPolicy {
+    #[inline]
+    fn ignores_extra_pops(self) -> bool {
+        matches!(self, BranchPolicy::IgnoreExtraPops | BranchPolicy::Lenient)
+    }
+
+    #[inline]
+    fn auto_closes(self) -> bool {
+        matches!(self, BranchPolicy::AutoClose | BranchPolicy::Lenient)
+    }
 }
 
 impl Default for AbopTurtleInterpretation {
@@ -52,8 +221,110 @@ impl AbopTurtleInterpretation {
     pub fn new(n: usize, delta: f32) -> Self {
         Self {
             n,
-            delta
+            delta,
+            heading: 90.0,
+            step: 5.0,
+            width: None,
+            width_scale: 1.0,
+            angle_scale: 1.0,
+            length_scale: 1.0,
+            branch_policy: BranchPolicy::default(),
+            actions: HashMap::new()
+        }
+    }
+
+    /// Returns this interpretation starting the turtle facing the given direction, in degrees
+    /// anticlockwise from the x-axis. This defaults to 90, so that the turtle faces up.
+    pub fn with_heading(mut self, heading: f64) -> Self {
+        self.heading = heading;
+        self
+    }
+
+    /// Returns this interpretation moving the turtle by the given distance for each step.
+    /// This defaults to 5.
+    pub fn with_step(mut self, step: f64) -> Self {
+        self.step = step;
+        self
+    }
+
+    /// Returns this interpretation drawing lines of the given width. Without a width, lines are
+    /// drawn as thinly as the output allows.
+    pub fn with_width(mut self, width: f64) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Returns this interpretation multiplying the line width by `scale` for every
+    /// [`TurtleAction::ScaleWidth`]. This defaults to 1, leaving the width unchanged.
+    pub fn with_width_scale(mut self, scale: f64) -> Self {
+        self.width_scale = scale;
+        self
+    }
+
+    /// Returns this interpretation multiplying the turning angle by `scale` for every
+    /// [`TurtleAction::ScaleAngle`]. This defaults to 1, leaving the angle unchanged.
+    pub fn with_angle_scale(mut self, scale: f64) -> Self {
+        self.angle_scale = scale;
+        self
+    }
+
+    /// Returns this interpretation multiplying the step length by `scale` for every
+    /// [`TurtleAction::ScaleLength`]. This defaults to 1, leaving the length unchanged.
+    pub fn with_length_scale(mut self, scale: f64) -> Self {
+        self.length_scale = scale;
+        self
+    }
+
+    /// Returns this interpretation performing `action` whenever it reaches `symbol`.
+    ///
+    /// Once any action has been given, the symbols of the [`abop_family`] are no longer required
+    /// to be registered, although they keep their usual actions when they are.
+    pub fn with_action(mut self, symbol: Symbol, action: TurtleAction) -> Self {
+        self.actions.insert(symbol, action);
+        self
+    }
+
+    /// Returns this interpretation performing the actions given to the family's symbols.
+    /// See [`Builder::with_action`](crate::system::family::Builder::with_action).
+    ///
+    /// ```
+    /// use rusty_systems::prelude::*;
+    /// use rusty_systems::interpretation::abop::AbopTurtleInterpretation;
+    /// use rusty_systems::system::family::parser::parse;
+    ///
+    /// let family = parse("name: Steps\nproduction Step = forward\nterminal Turn = left").unwrap();
+    /// let interpretation = AbopTurtleInterpretation::default().with_family_actions(&family).unwrap();
+    ///
+    /// let system = System::of_family(family).unwrap();
+    /// let paths = interpretation.interpret(&system, &"Step Turn Step".parse().unwrap()).unwrap();
+    /// assert_eq!(paths[0].len(), 3);
+    /// ```
+    ///
+    /// Fails with an [`ErrorKind::Parse`] error if an action is not one of the [`TurtleAction`] names.
+    pub fn with_family_actions(mut self, family: &SystemFamily) -> crate::Result<Self> {
+        for symbol in family.symbols() {
+            if let Some(action) = &symbol.action {
+                self.actions.insert(Symbol::build(&symbol.name)?, action.parse()?);
+            }
         }
+
+        Ok(self)
+    }
+
+    /// The actions that have been given to symbols. See [`AbopTurtleInterpretation::with_action`].
+    pub fn actions(&self) -> &HashMap<Symbol, TurtleAction> {
+        &self.actions
+    }
+
+    /// Returns this interpretation using the given policy for unbalanced branches.
+    pub fn with_branch_policy(mut self, policy: BranchPolicy) -> Self {
+        self.branch_policy = policy;
+        self
+    }
+
+    /// How unbalanced branches are handled. See [`BranchPolicy`].
+    pub fn branch_policy(&self) -> BranchPolicy {
+        self.branch_policy
     }
 
     /// The number of rewite iterations that a system should perform
@@ -67,68 +338,159 @@ impl AbopTurtleInterpretation {
     pub fn delta(&self) -> f32 {
         self.delta
     }
-}
 
-pub type AbopSvgInterpretation = SvgPathInterpretation<AbopTurtleInterpretation>;
+    /// The direction the turtle starts facing, in degrees anticlockwise from the x-axis.
+    pub fn heading(&self) -> f64 {
+        self.heading
+    }
 
-impl Interpretation for AbopTurtleInterpretation {
-    type Item = Vec<Path>;
+    /// The distance the turtle moves for each step.
+    pub fn step(&self) -> f64 {
+        self.step
+    }
 
-    fn system() -> crate::Result<System> {
-        let family = get_or_init_family("ABOP", abop_family);
-        System::of_family(family)
+    /// The width of the lines, if one has been given.
+    pub fn width(&self) -> Option<f64> {
+        self.width
     }
 
-    fn interpret<S: SymbolStore>(&self,
-                                 tokens: &S,
-                                 string: &ProductionString) -> crate::Result<Self::Item> {
-        // We need token values to interpret the strings.
-        let forward = tokens.get_symbol("Forward").unwrap();
-        let space = tokens.get_symbol("Move").unwrap();
-        let right = tokens.get_symbol("+").unwrap();
-        let left = tokens.get_symbol("-").unwrap();
-        let push = tokens.get_symbol("[").unwrap();
-        let pop = tokens.get_symbol("]").unwrap();
-
-        // We will interpret the tokens as instructions to a LOGO turtle. The following
-        // variables keep track of the position that we're at and the direction we're facing.
-        // the stack is for the push / pop tokens.
-        let mut pos_stack: Vec<(Point, Vector)> = Vec::new();
-        let mut pos = Point::zero();
-        let mut dir = Vector::new(0.0, 5.0);
-        let angle: f64 = self.delta() as f64; // degrees
-
-        // Every time we "branch" (using push and pop), we start a new path.
+    /// How much the line width is scaled by. See [`TurtleAction::ScaleWidth`].
+    pub fn width_scale(&self) -> f64 {
+        self.width_scale
+    }
+
+    /// How much the turning angle is scaled by. See [`TurtleAction::ScaleAngle`].
+    pub fn angle_scale(&self) -> f64 {
+        self.angle_scale
+    }
+
+    /// How much the step length is scaled by. See [`TurtleAction::ScaleLength`].
+    pub fn length_scale(&self) -> f64 {
+        self.length_scale
+    }
+
+    /// Returns the location of every query symbol, `?P`, in the string. See the
+    /// [environment module](crate::system::environment).
+    ///
+    /// If `?P` has not been registered with `tokens`, there are no queries.
+    pub fn locate_queries<S: SymbolStore>(&self, tokens: &S, string: &ProductionString) -> crate::Result<Vec<Query>> {
+        let query = match tokens.get_symbol(QUERY) {
+            None => return Ok(Vec::new()),
+            Some(query) => query
+        };
+
+        let mut queries = Vec::new();
+        self.walk(tokens, string, |index, symbol, position, heading| {
+            if symbol == query {
+                queries.push(Query { index, symbol, position, heading });
+            }
+        })?;
+
+        Ok(queries)
+    }
+
+    /// Moves the turtle over the string, as [`Interpretation::interpret`] does, but also calls
+    /// `visit` for every symbol with the symbol's index, the symbol, and the turtle's position and
+    /// heading when the symbol is reached.
+    ///
+    /// This returns the same paths as [`Interpretation::interpret`].
+    pub fn walk<S, F>(&self,
+                      tokens: &S,
+                      string: &ProductionString,
+                      visit: F) -> crate::Result<Vec<Path>>
+        where S: SymbolStore,
+              F: FnMut(usize, Symbol, Point, Vector)
+    {
+        self.trace(tokens, string.iter().map(|symbol| (*symbol, 1.0)), visit)
+    }
+
+    /// Interprets a string produced by a [timed derivation](crate::system::timed). The turtle
+    /// moves forward by a distance proportional to each symbol's [growth](crate::system::timed::TimedModule::growth),
+    /// so that partially grown segments are drawn partially grown.
+    pub fn interpret_timed<S: SymbolStore>(&self, tokens: &S, string: &TimedString) -> crate::Result<Vec<Path>> {
+        self.trace(tokens, string.iter().map(|module| (module.symbol, module.growth())), |_, _, _, _| {})
+    }
+
+    /// Moves the turtle over pairs of symbols and the fraction of a full step that the symbol
+    /// moves the turtle by.
+    fn trace<S, I, F>(&self,
+                      tokens: &S,
+                      symbols: I,
+                      mut visit: F) -> crate::Result<Vec<Path>>
+        where S: SymbolStore,
+              I: IntoIterator<Item=(Symbol, f64)>,
+              F: FnMut(usize, Symbol, Point, Vector)
+    {
+        let actions = self.resolve_actions(tokens)?;
+
+        // We will interpret the tokens as instructions to a LOGO turtle. The turtle
+        // keeps track of the position that we're at, the direction we're facing (whose length is the
+        // length of a step), and how we turn and draw. The stack is for the push / pop tokens,
+        // and records the turtle as it was when each branch started.
+        let mut stack: Vec<(Turtle, usize)> = Vec::new();
+        let mut turtle = Turtle {
+            pos: Point::zero(),
+            // Rotating from straight up keeps the default heading exact.
+            dir: Vector::new(0.0, self.step).rotate(self.heading - 90.0),
+            angle: self.delta() as f64, // degrees
+            width: self.width
+        };
+
+        // Every time we "branch" (using push and pop), or change the width, we start a new path.
         let mut paths: Vec<Path> = Vec::new();
+        let mut path = turtle.start_path();
 
-        let mut path = Path::new();
-        path.push(pos);
-
-        // todo indicate what productions / tokens haven't been used
-        for token in string {
-            if token == forward {                   // interpret forward tokens.
-                pos = pos + dir;
-                path.push(pos);
-            } else if token == space {
-                pos = pos + dir;
-                if path.len() > 1 {
-                    paths.push(path)
+        for (index, (token, scale)) in symbols.into_iter().enumerate() {
+            visit(index, token, turtle.pos, turtle.dir);
+
+            match actions.get(&token) {
+                Some(TurtleAction::Forward) => {
+                    turtle.pos = turtle.pos + turtle.dir * scale;
+                    path.push(turtle.pos);
+                }
+                Some(TurtleAction::Move) => {
+                    turtle.pos = turtle.pos + turtle.dir * scale;
+                    if path.len() > 1 {
+                        paths.push(path)
+                    }
+                    path = turtle.start_path();
+                }
+                Some(TurtleAction::Push) => {       // This starts "a branch" of the plant.
+                    stack.push((turtle, index));
                 }
-                path = Path::new();
-                path.push(pos);
-            } else if token == push {               // interpret push tokens. This starts "a branch" of the plant.
-                pos_stack.push((pos, dir));
-            } else if token == pop {                // interpret pop tokens. This ends "a branch", returning to where the branch started.
-                (pos, dir) = pos_stack.pop().expect("Nothing to pop");
-                if path.len() > 1 {
-                    paths.push(path)
+                Some(TurtleAction::Pop) => {        // This ends "a branch", returning to where the branch started.
+                    match stack.pop() {
+                        Some((t, _)) => turtle = t,
+                        None if self.branch_policy.ignores_extra_pops() => continue,
+                        None => return Err(Error::branching(index,
+                            format!("symbol {index} closes a branch that was never opened")))
+                    }
+                    if path.len() > 1 {
+                        paths.push(path)
+                    }
+                    path = turtle.start_path();
                 }
-                path = Path::new();
-                path.push(pos);
-            } else if token == left {               // Rotate a bit
-                dir = dir.rotate(-angle);
-            } else if token == right {              // Rotate in the other direction.
-                dir = dir.rotate(angle);
+                Some(TurtleAction::TurnLeft) => turtle.dir = turtle.dir.rotate(-turtle.angle),
+                Some(TurtleAction::TurnRight) => turtle.dir = turtle.dir.rotate(turtle.angle),
+                Some(TurtleAction::ScaleLength) => turtle.dir = turtle.dir * self.length_scale,
+                Some(TurtleAction::ScaleAngle) => turtle.angle *= self.angle_scale,
+                Some(TurtleAction::ScaleWidth) => {
+                    if let Some(width) = turtle.width {
+                        turtle.width = Some(width * self.width_scale);
+                        if path.len() > 1 {
+                            paths.push(path)
+                        }
+                        path = turtle.start_path();
+                    }
+                }
+                None => {}
+            }
+        }
+
+        if let Some((_, index)) = stack.last() {
+            if !self.branch_policy.auto_closes() {
+                return Err(Error::branching(*index,
+                    format!("the branch opened at symbol {index} is never closed")));
             }
         }
 
@@ -139,6 +501,89 @@ impl Interpretation for AbopTurtleInterpretation {
         Ok(paths)
     }
 
+    /// Looks up the symbols that the turtle acts on. Without any actions of its own, the
+    /// interpretation requires every symbol in [`TurtleAction::DEFAULTS`] to be registered,
+    /// apart from those of the scaling actions.
+    fn resolve_actions<S: SymbolStore>(&self, tokens: &S) -> crate::Result<HashMap<Symbol, TurtleAction>> {
+        let mut actions = HashMap::with_capacity(TurtleAction::DEFAULTS.len() + self.actions.len());
+
+        for (name, action) in TurtleAction::DEFAULTS {
+            match tokens.get_symbol(name) {
+                Some(symbol) => { actions.insert(symbol, action); }
+                None if !self.actions.is_empty() || !action.is_required() => {}
+                None => return Err(Error::definition(
+                    format!("the symbol [{name}] is required by the turtle interpretation, but has not been registered")))
+            }
+        }
+
+        actions.extend(self.actions.iter().map(|(symbol, action)| (*symbol, *action)));
+        Ok(actions)
+    }
+}
+
+/// The state of the turtle as it moves over a string.
+#[derive(Debug, Copy, Clone)]
+struct Turtle {
+    pos: Point,
+    dir: Vector,
+    /// in degrees
+    angle: f64,
+    width: Option<f64>
+}
+
+impl Turtle {
+    /// A path starting at the turtle's position, drawn with its width.
+    fn start_path(&self) -> Path {
+        let mut path = match self.width {
+            Some(width) => Path::new().with_width(width),
+            None => Path::new()
+        };
+        path.push(self.pos);
+        path
+    }
+}
+
+/// Locates queries as [`AbopTurtleInterpretation::locate_queries`] does, for use by
+/// [`System::derive_open`].
+impl QueryLocator for AbopTurtleInterpretation {
+    fn locate_queries(&self, system: &System, string: &ProductionString) -> crate::Result<Vec<Query>> {
+        AbopTurtleInterpretation::locate_queries(self, system, string)
+    }
+}
+
+pub type AbopSvgInterpretation = SvgPathInterpretation<AbopTurtleInterpretation>;
+
+impl Interpretation for AbopTurtleInterpretation {
+    type Item = Vec<Path>;
+
+    /// Returns a system of the ABOP family, which uses `%` to cut branches.
+    /// See [`System::with_cut_symbol`].
+    fn system() -> crate::Result<System> {
+        let family = get_or_init_family("ABOP", abop_family);
+        Ok(System::of_family(family)?.with_cut_symbol(Symbol::build("%")?, Symbol::build("[")?, Symbol::build("]")?))
+    }
+
+    fn interpret<S: SymbolStore>(&self,
+                                 tokens: &S,
+                                 string: &ProductionString) -> crate::Result<Self::Item> {
+        self.walk(tokens, string, |_, _, _, _| {})
+    }
+
+    /// Moves the turtle over the symbols as they are produced, so that only the paths
+    /// are held in memory.
+    fn interpret_symbols<S, I>(&self, tokens: &S, string: I) -> crate::Result<Self::Item>
+        where S: SymbolStore,
+              I: IntoIterator<Item=Symbol>
+    {
+        self.trace(tokens, string.into_iter().map(|symbol| (symbol, 1.0)), |_, _, _, _| {})
+    }
+
+    fn interpreted_symbols(&self) -> Vec<String> {
+        let mut symbols: Vec<String> = ["Forward", "Move", "+", "-", "[", "]", "!", "\"", "@", QUERY].map(String::from).to_vec();
+        symbols.extend(self.actions.keys().map(|s| s.to_string()));
+        symbols
+    }
+
     fn run_settings(&self) -> RunSettings {
         #[allow(clippy::needless_update)]
         RunSettings {
@@ -154,7 +599,8 @@ impl Interpretation for AbopTurtleInterpretation {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::interpretation::Interpretation;
+    use crate::error::ErrorKind;
+    use crate::interpretation::{abop, Interpretation};
     use crate::parser::parse_prod_string;
     use crate::productions::ProductionStore;
 
@@ -174,4 +620,146 @@ mod tests {
         assert_eq!(result.len(), 3)
     }
 
+    #[test]
+    fn system_cuts_branches() {
+        let system = AbopTurtleInterpretation::system().unwrap();
+        system.add_production("X -> Forward [ + X % Forward ] Forward").unwrap();
+
+        let string = parse_prod_string("X").unwrap();
+        let string = system.derive(string, RunSettings::for_max_iterations(2)).unwrap();
+        assert_eq!(string, parse_prod_string("Forward [ + Forward [ + X ] Forward ] Forward").unwrap());
+    }
+
+    #[test]
+    fn unbalanced_branches() {
+        let system = AbopTurtleInterpretation::system().unwrap();
+        let extra_pop = parse_prod_string("Forward [ Forward ] ] Forward").unwrap();
+        let unclosed = parse_prod_string("Forward [ Forward [ Forward ] Forward").unwrap();
+
+        let strict = AbopTurtleInterpretation::default();
+        let error = strict.interpret(&system, &extra_pop).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::Branching));
+        assert_eq!(error.index(), Some(4));
+
+        let error = strict.interpret(&system, &unclosed).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::Branching));
+        assert_eq!(error.index(), Some(1));
+
+        let ignore = AbopTurtleInterpretation::default().with_branch_policy(BranchPolicy::IgnoreExtraPops);
+        assert!(ignore.interpret(&system, &extra_pop).is_ok());
+        assert!(ignore.interpret(&system, &unclosed).is_err());
+
+        let close = AbopTurtleInterpretation::default().with_branch_policy(BranchPolicy::AutoClose);
+        assert!(close.interpret(&system, &extra_pop).is_err());
+        assert!(close.interpret(&system, &unclosed).is_ok());
+
+        let lenient = AbopTurtleInterpretation::default().with_branch_policy(BranchPolicy::Lenient);
+        assert!(lenient.interpret(&system, &extra_pop).is_ok());
+        assert!(lenient.interpret(&system, &unclosed).is_ok());
+    }
+
+    #[test]
+    fn timed_segments_grow() {
+        let system = AbopTurtleInterpretation::system().unwrap();
+        system.add_production("X -> Forward [ + X ] X").unwrap();
+
+        let mut growth = system.timed(parse_prod_string("X").unwrap(), RunSettings::default())
+            .with_lifetime(Symbol::build("X").unwrap(), 1.0)
+            .with_lifetime(Symbol::build("Forward").unwrap(), 2.0);
+        growth.advance(1.5).unwrap();
+
+        let paths = AbopTurtleInterpretation::default().interpret_timed(&system, growth.string()).unwrap();
+        let end = *paths[0].get(1).unwrap();
+        assert!((end.y() - 5.0 * 0.25).abs() < 0.0001);
+    }
+
+    #[test]
+    fn interprets_streamed_symbols() {
+        let (interpretation, system, axiom) = library::parse("fig1_24d").unwrap();
+        let string = system.derive(axiom.clone(), interpretation.run_settings()).unwrap();
+        let stream = system.derive_streaming(axiom, interpretation.run_settings()).unwrap();
+
+        let expected = interpretation.interpret(&system, &string).unwrap();
+        assert_eq!(interpretation.interpret_symbols(&system, stream).unwrap(), expected);
+    }
+
+    #[test]
+    fn custom_actions() {
+        let system = System::new();
+        system.add_production("Stem -> Stem Stem").unwrap();
+
+        let string = parse_prod_string("Stem Open Turn Stem Close Stem").unwrap();
+        let interpretation = AbopTurtleInterpretation::new(1, 90.0)
+            .with_action(Symbol::build("Stem").unwrap(), TurtleAction::Forward)
+            .with_action(Symbol::build("Turn").unwrap(), "right".parse().unwrap())
+            .with_action(Symbol::build("Open").unwrap(), TurtleAction::Push)
+            .with_action(Symbol::build("Close").unwrap(), TurtleAction::Pop);
+
+        let paths = interpretation.interpret(&system, &string).unwrap();
+        assert_eq!(paths.len(), 2);
+        assert_eq!(paths[1].len(), 2);
+
+        assert!("sideways".parse::<TurtleAction>().is_err());
+        for (_, action) in TurtleAction::DEFAULTS {
+            assert_eq!(action.to_string().parse::<TurtleAction>().unwrap(), action);
+        }
+    }
+
+    #[test]
+    fn heading_step_and_scales() {
+        let system = AbopTurtleInterpretation::system().unwrap();
+        let close = |a: f64, b: f64| (a - b).abs() < 0.0001;
+
+        let interpretation = AbopTurtleInterpretation::new(1, 90.0)
+            .with_heading(0.0)
+            .with_step(2.0)
+            .with_length_scale(0.5)
+            .with_angle_scale(0.5);
+
+        // Right, then up by half a step, then turning by half the angle.
+        let string = parse_prod_string("Forward \" + Forward @ - Forward").unwrap();
+        let paths = interpretation.interpret(&system, &string).unwrap();
+        let points: Vec<Point> = paths[0].iter().copied().collect();
+        assert!(close(points[1].x(), 2.0) && close(points[1].y(), 0.0));
+        assert!(close(points[2].x(), 2.0) && close(points[2].y(), 1.0));
+        assert!(close(points[3].x(), 2.0 + 45.0_f64.to_radians().sin()));
+
+        // Branches restore the step length.
+        let string = parse_prod_string("[ \" ] Forward").unwrap();
+        let paths = interpretation.interpret(&system, &string).unwrap();
+        assert!(close(paths.last().unwrap()[1].x(), 2.0));
+    }
+
+    #[test]
+    fn widths() {
+        let system = AbopTurtleInterpretation::system().unwrap();
+        let string = parse_prod_string("Forward ! Forward [ ! Forward ] Forward").unwrap();
+
+        let paths = AbopTurtleInterpretation::default().interpret(&system, &string).unwrap();
+        assert!(paths.iter().all(|path| path.width().is_none()));
+
+        let paths = AbopTurtleInterpretation::default()
+            .with_width(2.0)
+            .with_width_scale(0.5)
+            .interpret(&system, &string).unwrap();
+        let widths: Vec<f64> = paths.iter().filter(|p| p.len() > 1).map(|p| p.width().unwrap()).collect();
+        assert_eq!(widths, [2.0, 1.0, 0.5, 1.0]);
+    }
+
+    #[test]
+    fn missing_symbols_are_errors() {
+        let system = System::new();
+        system.add_production("Forward -> Forward Forward").unwrap();
+
+        let string = parse_prod_string("Forward").unwrap();
+        let error = AbopTurtleInterpretation::default_interpret(&system, &string).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::Definitions));
+    }
+
+    #[test]
+    fn abop_available() {
+        let abop = get_or_init_family("ABOP", abop::abop_family);
+        assert_eq!(abop.name(), "ABOP");
+    }
+
 }
\ No newline at end of file
@@ -0,0 +1,211 @@
+//! Classic figures from chapter 1 of *the Algorithmic Beauty of Plants*, written in
+//! the [plant format](crate::interpretation::abop::parser).
+//!
+//! Each [`Figure`] is found by name, and can be parsed ready to derive:
+//!
+//! ```
+//! use rusty_systems::prelude::*;
+//! use rusty_systems::interpretation::abop::library;
+//!
+//! let (interpretation, system, axiom) = library::parse("fig1_24a").unwrap();
+//! let string = system.derive(axiom, interpretation.run_settings()).unwrap();
+//! let paths = interpretation.interpret(&system, &string).unwrap();
+//! assert!(!paths.is_empty());
+//! ```
+//!
+//! The figures use the number of iterations and the angles given in the book.
+//! Where the book uses two symbols that both draw lines, such as the `Fl` and `Fr` of the
+//! Sierpiński gasket, the figures follow a symbol that does not draw with `Forward`.
+//!
+//! [abop]: http://algorithmicbotany.org/papers/#abop
+
+use crate::error::Error;
+use super::parser::{self, ParsedAbop};
+
+/// A figure from [the Algorithmic Beauty of Plants][abop].
+///
+/// [abop]: http://algorithmicbotany.org/papers/#abop
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Figure {
+    /// The name that the figure is found by.
+    pub name: &'static str,
+    /// What the figure shows, and where it is in the book.
+    pub description: &'static str,
+    /// The figure in the plant format.
+    pub plant: &'static str
+}
+
+/// Every figure in the library.
+pub const FIGURES: [Figure; 12] = [
+    Figure {
+        name: "koch_island",
+        description: "Quadratic Koch island (fig 1.7a, pg 9)",
+        plant: "n = 2
+delta = 90
+initial: Forward - Forward - Forward - Forward
+Forward -> Forward + Forward Forward - Forward Forward - Forward - Forward + Forward + Forward Forward - Forward - Forward + Forward + Forward Forward + Forward Forward - Forward"
+    },
+    Figure {
+        name: "koch_curve_a",
+        description: "Quadratic Koch curve (fig 1.9a, pg 10)",
+        plant: "n = 4
+delta = 90
+initial: Forward - Forward - Forward - Forward
+Forward -> Forward Forward - Forward - Forward - Forward - Forward - Forward + Forward"
+    },
+    Figure {
+        name: "koch_curve_b",
+        description: "Quadratic Koch curve (fig 1.9b, pg 10)",
+        plant: "n = 4
+delta = 90
+initial: Forward - Forward - Forward - Forward
+Forward -> Forward Forward - Forward - Forward - Forward - Forward Forward"
+    },
+    Figure {
+        name: "dragon_curve",
+        description: "Dragon curve (fig 1.10a, pg 11)",
+        plant: "n = 10
+delta = 90
+initial: Forward X
+X -> X + Y Forward +
+Y -> - Forward X - Y"
+    },
+    Figure {
+        name: "sierpinski_gasket",
+        description: "Sierpiński gasket (fig 1.10b, pg 11)",
+        plant: "n = 6
+delta = 60
+initial: R Forward
+L -> R Forward + L Forward + R
+R -> L Forward - R Forward - L"
+    },
+    Figure {
+        name: "hilbert_curve",
+        description: "Hilbert curve",
+        plant: "n = 5
+delta = 90
+initial: X
+X -> + Y Forward - X Forward X - Forward Y +
+Y -> - X Forward + Y Forward Y + Forward X -"
+    },
+    Figure {
+        name: "fig1_24a",
+        description: "Bracketed plant a (fig 1.24a, pg 25)",
+        plant: "n = 5
+delta = 25.7
+initial: Forward
+Forward -> Forward [ + Forward ] Forward [ - Forward ] Forward"
+    },
+    Figure {
+        name: "fig1_24b",
+        description: "Bracketed plant b (fig 1.24b, pg 25)",
+        plant: "n = 5
+delta = 20
+initial: Forward
+Forward -> Forward [ + Forward ] Forward [ - Forward ] [ Forward ]"
+    },
+    Figure {
+        name: "fig1_24c",
+        description: "Bracketed plant c (fig 1.24c, pg 25)",
+        plant: "n = 4
+delta = 22.5
+initial: Forward
+Forward -> Forward Forward - [ - Forward + Forward + Forward ] + [ + Forward - Forward - Forward ]"
+    },
+    Figure {
+        name: "fig1_24d",
+        description: "Bracketed plant d (fig 1.24d, pg 25)",
+        plant: "n = 7
+delta = 20
+initial: X
+X -> Forward [ + X ] Forward [ - X ] + X
+Forward -> Forward Forward"
+    },
+    Figure {
+        name: "fig1_24e",
+        description: "Bracketed plant e (fig 1.24e, pg 25)",
+        plant: "n = 7
+delta = 25.7
+initial: X
+X -> Forward [ + X ] [ - X ] Forward X
+Forward -> Forward Forward"
+    },
+    Figure {
+        name: "fig1_24f",
+        description: "Bracketed plant f (fig 1.24f, pg 25)",
+        plant: "n = 5
+delta = 22.5
+initial: X
+X -> Forward - [ [ X ] + X ] + Forward [ + Forward X ] - X
+Forward -> Forward Forward"
+    }
+];
+
+/// Returns the figure with the given name, if there is one.
+pub fn figure(name: &str) -> Option<&'static Figure> {
+    FIGURES.iter().find(|figure| figure.name == name)
+}
+
+/// Returns the names of every figure in the library.
+pub fn names() -> impl Iterator<Item=&'static str> {
+    FIGURES.iter().map(|figure| figure.name)
+}
+
+/// Parses the figure with the given name. See [`parser::parse`].
+///
+/// Fails with an [`ErrorKind::Definitions`](crate::error::ErrorKind::Definitions) error if there is
+/// no figure with that name.
+pub fn parse(name: &str) -> crate::Result<ParsedAbop> {
+    let figure = figure(name).ok_or_else(|| Error::definition(
+        format!("there is no figure named [{name}]. The figures are: {}", names().collect::<Vec<_>>().join(", "))))?;
+    parser::parse(figure.plant)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Bounds;
+    use crate::interpretation::Interpretation;
+
+    /// The length of the derived string, and its bounds as (min x, max x, min y, max y).
+    fn derive(name: &str) -> (usize, [f64; 4]) {
+        let (interpretation, system, axiom) = parse(name).unwrap();
+        let string = system.derive(axiom, interpretation.run_settings()).unwrap();
+        let bounds = interpretation.interpret(&system, &string).unwrap().bounds().unwrap();
+
+        let round = |value: f64| (value * 100.0).round() / 100.0;
+        (string.len(), [round(bounds.min_x), round(bounds.max_x), round(bounds.min_y), round(bounds.max_y)])
+    }
+
+    #[test]
+    fn golden_figures() {
+        let golden: [(&str, usize, [f64; 4]); 12] = [
+            ("koch_island", 2211, [-70.0, 250.0, -70.0, 250.0]),
+            ("koch_curve_a", 30427, [-40.0, 660.0, -520.0, 180.0]),
+            ("koch_curve_b", 16007, [0.0, 405.0, 0.0, 405.0]),
+            ("dragon_curve", 4094, [-185.0, 50.0, -50.0, 105.0]),
+            ("sierpinski_gasket", 2186, [-272.8, 0.0, 0.0, 320.0]),
+            ("hilbert_curve", 3411, [-155.0, 0.0, 0.0, 155.0]),
+            ("fig1_24a", 7811, [-175.63, 231.29, 0.0, 1215.0]),
+            ("fig1_24b", 9373, [-61.89, 85.17, 0.0, 315.0]),
+            ("fig1_24c", 11116, [-55.83, 120.37, 0.0, 279.49]),
+            ("fig1_24d", 13956, [-350.51, 350.51, 0.0, 1244.67]),
+            ("fig1_24e", 12863, [-311.66, 311.66, 0.0, 1270.0]),
+            ("fig1_24f", 6263, [-179.37, 93.43, 0.0, 400.83])
+        ];
+
+        for (name, length, bounds) in golden {
+            assert_eq!(derive(name), (length, bounds), "{name}");
+        }
+    }
+
+    #[test]
+    fn every_figure_parses() {
+        for figure in FIGURES {
+            assert!(parser::parse(figure.plant).is_ok(), "{}", figure.name);
+        }
+        assert_eq!(names().count(), FIGURES.len());
+        assert!(parse("fig0_0").is_err());
+    }
+}
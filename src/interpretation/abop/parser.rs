@@ -15,6 +15,63 @@
 //! X -> Forward + [ [ X ] - X ] - Forward [ - Forward X ] + X
 //! ```
 //!
+//! Other settings change how the turtle draws:
+//!
+//! * `heading`, the direction the turtle starts facing, in degrees anticlockwise from the x-axis.
+//!   This defaults to 90, so that the turtle faces up.
+//! * `step`, the distance the turtle moves forward. This defaults to 5.
+//! * `width`, the width of the lines the turtle draws.
+//! * `width_scale`, `length_scale` and `angle_scale`, which the line width, step length and
+//!   turning angle are multiplied by when the turtle reaches `!`, `"` and `@` respectively.
+//!   These default to 1.
+//!
+//! Lengths, widths and scales must be positive.
+//!
+//! # Named systems
+//!
+//! A single string may hold several systems, each given a name and enclosed in braces.
+//! Settings given outside of the systems are shared by all of them, and each system
+//! may override them.
+//!
+//! ```plant
+//! delta = 25.7    # Shared by both systems
+//!
+//! system fig1_24a {
+//!     n = 5
+//!     initial: Forward
+//!     Forward -> Forward [ + Forward ] Forward [ - Forward ] Forward
+//! }
+//!
+//! system fig1_24b {
+//!     n = 5
+//!     delta = 20
+//!     initial: Forward
+//!     Forward -> Forward [ + Forward ] Forward [ - Forward ] [ Forward ]
+//! }
+//! ```
+//!
+//! Use [`parse_system`] to parse one of the systems, or [`parse_systems`] to parse
+//! them all.
+//!
+//! # Includes and constants
+//!
+//! The lines of another file can be included, as though they had been written in place
+//! of the include. Included files are found relative to the file including them.
+//! Files may not include themselves, directly or through other files.
+//!
+//! Constants can be defined once and then used as the value of any setting.
+//!
+//! ```plant
+//! include "common.plant"      # Might contain shared settings, constants and productions
+//! define LEAF_ANGLE = 30
+//!
+//! system leafy {
+//!     delta = LEAF_ANGLE
+//!     initial: X
+//!     include "branching.plant"
+//! }
+//! ```
+//!
 //! # Parsing
 //!
 //! If we have a string in the format given above, you can parse it like so:
@@ -30,10 +87,13 @@
 //! The [`parse`] function returns the following:
 //!
 //! * `interpretation`, being a [`AbopTurtleInterpretation`]. If you want to output SVG from
-//!    this, see [`SvgPathInterpretation`].
+//!   this, see [`SvgPathInterpretation`].
 //! * `system`, being a [`System`] ready to run.
 //! * `initial_string`, being the [`ProductionString`] the file specifies as the initial string.
 
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
 use crate::error::ErrorKind;
 use crate::interpretation::abop::*;
 use crate::parser::parse_prod_string;
@@ -43,7 +103,7 @@ use crate::productions::ProductionStore;
 /// specified in this library's "plant" format.
 ///
 /// * `interpretation`, being a [`AbopTurtleInterpretation`]. If you want to output SVG from
-///    this, see [`SvgPathInterpretation`].
+///   this, see [`SvgPathInterpretation`].
 /// * `system`, being a [`System`] ready to run.
 /// * `initial_string`, being the [`ProductionString`] the file specifies as the initial string.
 ///
@@ -55,70 +115,161 @@ use crate::productions::ProductionStore;
 ///
 /// let (interpretation, system, initial_string) = parse(plant_string).unwrap();
 /// ```
-type ParsedAbop = (AbopTurtleInterpretation, System, ProductionString);
+pub type ParsedAbop = (AbopTurtleInterpretation, System, ProductionString);
+
+/// The systems parsed from a string or file containing several named systems, ordered by name.
+/// See [`parse_systems`].
+pub type ParsedSystems = BTreeMap<String, ParsedAbop>;
+
+/// The name given to the system of a string that does not name its systems. See [`parse_systems`].
+pub const DEFAULT_SYSTEM: &str = "default";
 
 /// Parses a string in a bespoke "plant" format. See the [namespace](crate::interpretation::abop::parser)
 /// namespace documentation for more information.
 ///
+/// If the string contains more than one [named system](crate::interpretation::abop::parser#named-systems),
+/// this returns an [`ErrorKind::Parse`] error. Use [`parse_system`] or [`parse_systems`] for these.
+///
 /// See [`parse_file`] to parse a file containing a string in this format.
 pub fn parse(string: &str) -> crate::Result<ParsedAbop> {
-    let string = string.trim();
-    if string.is_empty() {
-        return Err(Error::new(ErrorKind::Parse, "String should not be empty"));
+    only_system(parse_systems(string)?)
+}
+
+/// Returns the only system, or an error if there are several.
+fn only_system(mut systems: ParsedSystems) -> crate::Result<ParsedAbop> {
+    if systems.len() > 1 {
+        let names: Vec<&str> = systems.keys().map(String::as_str).collect();
+        return Err(Error::new(ErrorKind::Parse,
+                              format!("The string contains several systems ({}), and one should be chosen", names.join(", "))));
     }
 
-    let mut lines = string.lines().peekable();
+    Ok(systems.pop_first().unwrap().1)
+}
+
+/// Parses the system with the given name from a string in the "plant" format.
+/// See [`parse_systems`].
+pub fn parse_system(string: &str, name: &str) -> crate::Result<ParsedAbop> {
+    named_system(parse_systems(string)?, name)
+}
 
-    let mut n = 2_usize;
-    let mut delta = 5.0_f32;
+fn named_system(mut systems: ParsedSystems, name: &str) -> crate::Result<ParsedAbop> {
+    systems.remove(name).ok_or_else(|| {
+        let names: Vec<&str> = systems.keys().map(String::as_str).collect();
+        Error::new(ErrorKind::Parse, format!("There is no system named [{name}]. The systems are: {}", names.join(", ")))
+    })
+}
 
-    let system = AbopTurtleInterpretation::system()?;
-    let mut prod_count = 0_usize;
-    let mut initial : Option<&str> = None;
+/// Parses every system in a string in the "plant" format, returning them by name.
+///
+/// A string without any [named systems](crate::interpretation::abop::parser#named-systems)
+/// contains a single system, called [`DEFAULT_SYSTEM`].
+///
+/// ```
+/// use rusty_systems::interpretation::abop::parser::{parse_systems, DEFAULT_SYSTEM};
+///
+/// let systems = parse_systems("initial: X\nX -> F F").unwrap();
+/// assert!(systems.contains_key(DEFAULT_SYSTEM));
+/// ```
+///
+/// Files [included](crate::interpretation::abop::parser#includes-and-constants) by the string are
+/// found relative to the current directory. See [`parse_systems_file`] to find them relative to a file.
+pub fn parse_systems(string: &str) -> crate::Result<ParsedSystems> {
+    let mut lines = Vec::new();
+    expand(string, None, &mut Vec::new(), &mut lines)?;
+    parse_lines(&lines)
+}
 
-    #[allow(clippy::while_let_on_iterator)]
-    while let Some(line) = lines.next() {
-        let line = remove_comment(line);
+/// Parses lines whose comments have been removed and whose includes have been expanded.
+fn parse_lines(lines: &[String]) -> crate::Result<ParsedSystems> {
+    if lines.iter().all(|line| line.is_empty()) {
+        return Err(Error::new(ErrorKind::Parse, "String should not be empty"));
+    }
+
+    let mut constants: HashMap<&str, &str> = HashMap::new();
+    let mut shared = Settings::default();
+    let mut unnamed = Definition::default();
+    let mut named: Vec<(&str, Definition)> = Vec::new();
+    let mut current: Option<(&str, Definition)> = None;
+
+    for line in lines {
+        let line = line.as_str();
         if line.is_empty() {
             continue;
         }
 
+        if let Some(constant) = parse_define(line)? {
+            let value = constants.get(constant.value).copied().unwrap_or(constant.value);
+            if constants.insert(constant.name, value).is_some() {
+                return Err(Error::new(ErrorKind::Parse, format!("The constant [{}] is defined more than once", constant.name)));
+            }
+            continue;
+        }
+
+        if let Some(name) = parse_system_start(line)? {
+            if let Some((open, _)) = current {
+                return Err(Error::new(ErrorKind::Parse, format!("System [{name}] starts before system [{open}] has finished")));
+            }
+            if named.iter().any(|(existing, _)| *existing == name) {
+                return Err(Error::new(ErrorKind::Parse, format!("System [{name}] is defined more than once")));
+            }
+
+            current = Some((name, Definition::default()));
+            continue;
+        }
+
+        if line == "}" {
+            match current.take() {
+                Some(system) => named.push(system),
+                None => return Err(Error::new(ErrorKind::Parse, "Found a closing brace outside of a system"))
+            }
+            continue;
+        }
+
         if is_equality_line(line) {
-            let equality = parse_equality(line)?;
-            match equality.name {
-                "n" | "N" => {
-                    n = equality.value.parse()?;
-                }
-                "d" | "D" | "delta" | "∂" => {
-                    delta = equality.value.parse()?;
-                }
-                _ => return Err(Error::new(ErrorKind::Parse, format!("Unrecognised line {}", line)))
+            let settings = match &mut current {
+                Some((_, definition)) => &mut definition.settings,
+                None => &mut shared
+            };
+            let mut equality = parse_equality(line)?;
+            if let Some(value) = constants.get(equality.value) {
+                equality.value = value;
             }
 
+            settings.set(equality.name, equality.value)?;
             continue;
         }
 
+        let definition = match &mut current {
+            Some((_, definition)) => definition,
+            None => &mut unnamed
+        };
+
         if is_initial(line) {
-            initial = Some(parse_initial(line));
+            definition.initial = Some(parse_initial(line));
             continue;
         }
 
-        prod_count += 1;
-        system.add_production(line)?;
+        definition.productions.push(line);
     }
 
-    if prod_count == 0 {
-        return Err(Error::new(ErrorKind::Parse, "No productions have been supplied"));
+    if let Some((name, _)) = current {
+        return Err(Error::new(ErrorKind::Parse, format!("System [{name}] is never closed")));
     }
 
-    if initial.is_none() {
-        return Err(Error::new(ErrorKind::Parse, "No initial axiom has been supplied"));
+    if named.is_empty() {
+        let system = unnamed.build(&shared)?;
+        return Ok(ParsedSystems::from([(DEFAULT_SYSTEM.to_string(), system)]));
     }
 
-    let initial = parse_prod_string(initial.unwrap())?;
+    if unnamed.initial.is_some() || !unnamed.productions.is_empty() {
+        return Err(Error::new(ErrorKind::Parse, "Axioms and productions must be inside a system when a string has named systems"));
+    }
 
-    let interpretation = AbopTurtleInterpretation::new(n, delta);
-    Ok((interpretation, system, initial))
+    named.into_iter()
+        .map(|(name, definition)| definition.build(&shared)
+            .map(|system| (name.to_string(), system))
+            .map_err(|e| e.with_context(format!("System [{name}]"))))
+        .collect()
 }
 
 /// Reads a file containing an L-System written in the library's bespoke "plant" format.
@@ -126,11 +277,227 @@ pub fn parse(string: &str) -> crate::Result<ParsedAbop> {
 /// See the [module documentation](crate::interpretation::abop::parser) for more information,
 /// as well as the [`parser`] function.
 pub fn parse_file<P: AsRef<std::path::Path>>(name: P) -> crate::Result<ParsedAbop> {
-    let contents = std::fs::read_to_string(name)?;
-    parse(&contents)
+    only_system(parse_systems_file(name)?)
+}
+
+/// Reads the system with the given name from a file in the "plant" format. See [`parse_system`].
+pub fn parse_system_file<P: AsRef<std::path::Path>>(path: P, name: &str) -> crate::Result<ParsedAbop> {
+    named_system(parse_systems_file(path)?, name)
+}
+
+/// Reads every system from a file in the "plant" format. See [`parse_systems`].
+///
+/// Files that this file includes are found relative to it.
+pub fn parse_systems_file<P: AsRef<std::path::Path>>(path: P) -> crate::Result<ParsedSystems> {
+    let path = path.as_ref().canonicalize()?;
+    let contents = std::fs::read_to_string(&path)?;
+
+    let mut lines = Vec::new();
+    expand(&contents, Some(&path), &mut vec![path.clone()], &mut lines)?;
+    parse_lines(&lines)
+}
+
+/// Removes comments from the lines of `string`, replacing every include with the lines of the
+/// included file. `file` is the file that `string` was read from, if any, and `including` holds
+/// the files currently being included, so that cycles can be found.
+fn expand(string: &str, file: Option<&Path>, including: &mut Vec<PathBuf>, lines: &mut Vec<String>) -> crate::Result<()> {
+    for line in string.lines() {
+        let line = remove_comment(line);
+        let included = match parse_include(line)? {
+            None => {
+                lines.push(line.to_string());
+                continue;
+            }
+            Some(included) => included
+        };
+
+        let path = match file.and_then(Path::parent) {
+            Some(directory) => directory.join(included),
+            None => PathBuf::from(included)
+        };
+        let path = path.canonicalize()
+            .map_err(|e| Error::new(ErrorKind::Io, format!("Unable to include [{included}]: {e}")))?;
+
+        if including.contains(&path) {
+            let cycle: Vec<String> = including.iter()
+                .chain(std::iter::once(&path))
+                .map(|p| p.to_string_lossy().to_string())
+                .collect();
+            return Err(Error::new(ErrorKind::Parse, format!("Files include each other in a cycle: {}", cycle.join(" -> "))));
+        }
+
+        let contents = std::fs::read_to_string(&path)?;
+        including.push(path.clone());
+        expand(&contents, Some(&path), including, lines)?;
+        including.pop();
+    }
+
+    Ok(())
 }
 
 
+/// Settings that may be given for every system in a string, and overridden by a single system.
+#[derive(Debug, Clone, Default)]
+struct Settings {
+    n: Option<usize>,
+    delta: Option<f32>,
+    heading: Option<f64>,
+    step: Option<f64>,
+    width: Option<f64>,
+    width_scale: Option<f64>,
+    angle_scale: Option<f64>,
+    length_scale: Option<f64>
+}
+
+impl Settings {
+    /// Sets the named setting from the text of its value, which must be valid for that setting.
+    fn set(&mut self, name: &str, value: &str) -> crate::Result<()> {
+        let number = |requirement: fn(f64) -> bool, description: &str| -> crate::Result<f64> {
+            match value.parse::<f64>() {
+                Ok(number) if number.is_finite() && requirement(number) => Ok(number),
+                _ => Err(Error::new(ErrorKind::Parse, format!("{name} should be {description}, not [{value}]")))
+            }
+        };
+
+        match name {
+            "n" | "N" => {
+                self.n = Some(value.parse().map_err(|_|
+                    Error::new(ErrorKind::Parse, format!("{name} should be a whole number of iterations, not [{value}]")))?);
+            }
+            "d" | "D" | "delta" | "∂" => self.delta = Some(number(|_| true, "an angle in degrees")? as f32),
+            "heading" => self.heading = Some(number(|_| true, "an angle in degrees")?),
+            "step" => self.step = Some(number(|n| n > 0.0, "a positive length")?),
+            "width" => self.width = Some(number(|n| n > 0.0, "a positive width")?),
+            "width_scale" => self.width_scale = Some(number(|n| n > 0.0, "a positive number")?),
+            "angle_scale" => self.angle_scale = Some(number(|n| n > 0.0, "a positive number")?),
+            "length_scale" => self.length_scale = Some(number(|n| n > 0.0, "a positive number")?),
+            _ => return Err(Error::new(ErrorKind::Parse, format!(
+                "Unrecognised setting [{name}]. The settings are n, delta, heading, step, width, width_scale, angle_scale and length_scale")))
+        }
+
+        Ok(())
+    }
+
+    /// Returns these settings, using `shared` for any that have not been given.
+    fn or(&self, shared: &Settings) -> Settings {
+        Settings {
+            n: self.n.or(shared.n),
+            delta: self.delta.or(shared.delta),
+            heading: self.heading.or(shared.heading),
+            step: self.step.or(shared.step),
+            width: self.width.or(shared.width),
+            width_scale: self.width_scale.or(shared.width_scale),
+            angle_scale: self.angle_scale.or(shared.angle_scale),
+            length_scale: self.length_scale.or(shared.length_scale)
+        }
+    }
+
+    /// Creates an interpretation using these settings, and the interpretation's defaults for
+    /// any that have not been given.
+    fn interpretation(&self) -> AbopTurtleInterpretation {
+        let mut interpretation = AbopTurtleInterpretation::new(self.n.unwrap_or(2), self.delta.unwrap_or(5.0));
+
+        if let Some(heading) = self.heading {
+            interpretation = interpretation.with_heading(heading);
+        }
+        if let Some(step) = self.step {
+            interpretation = interpretation.with_step(step);
+        }
+        if let Some(width) = self.width {
+            interpretation = interpretation.with_width(width);
+        }
+        if let Some(scale) = self.width_scale {
+            interpretation = interpretation.with_width_scale(scale);
+        }
+        if let Some(scale) = self.angle_scale {
+            interpretation = interpretation.with_angle_scale(scale);
+        }
+        if let Some(scale) = self.length_scale {
+            interpretation = interpretation.with_length_scale(scale);
+        }
+
+        interpretation
+    }
+}
+
+/// The lines that define a single system.
+#[derive(Debug, Default)]
+struct Definition<'a> {
+    settings: Settings,
+    initial: Option<&'a str>,
+    productions: Vec<&'a str>
+}
+
+impl Definition<'_> {
+    fn build(&self, shared: &Settings) -> crate::Result<ParsedAbop> {
+        let settings = self.settings.or(shared);
+
+        let system = AbopTurtleInterpretation::system()?;
+        for production in &self.productions {
+            system.add_production(*production)?;
+        }
+
+        if self.productions.is_empty() {
+            return Err(Error::new(ErrorKind::Parse, "No productions have been supplied"));
+        }
+
+        let initial = match self.initial {
+            None => return Err(Error::new(ErrorKind::Parse, "No initial axiom has been supplied")),
+            Some(initial) => parse_prod_string(initial)?
+        };
+
+        Ok((settings.interpretation(), system, initial))
+    }
+}
+
+/// Returns the quoted file name if the line is an include, such as `include "common.plant"`.
+fn parse_include(line: &str) -> crate::Result<Option<&str>> {
+    let rest = match line.strip_prefix("include") {
+        Some(rest) if rest.starts_with(char::is_whitespace) && !rest.contains("->") => rest.trim(),
+        _ => return Ok(None)
+    };
+
+    match rest.strip_prefix('"').and_then(|rest| rest.strip_suffix('"')) {
+        Some(file) if !file.is_empty() => Ok(Some(file)),
+        _ => Err(Error::new(ErrorKind::Parse, format!("Expected a quoted file name to include: {line}")))
+    }
+}
+
+/// Returns the constant if the line defines one, such as `define LEAF_ANGLE = 30`.
+fn parse_define(line: &str) -> crate::Result<Option<EqualityLine<'_>>> {
+    let rest = match line.strip_prefix("define") {
+        Some(rest) if rest.starts_with(char::is_whitespace) && !rest.contains("->") => rest,
+        _ => return Ok(None)
+    };
+
+    let constant = parse_equality(rest)
+        .map_err(|_| Error::new(ErrorKind::Parse, format!("Constants should be written as: define <name> = <value>: {line}")))?;
+
+    if constant.name.is_empty() || constant.name.contains(char::is_whitespace) || constant.value.is_empty() {
+        return Err(Error::new(ErrorKind::Parse, format!("Constants should be written as: define <name> = <value>: {line}")));
+    }
+
+    Ok(Some(constant))
+}
+
+/// Returns the name of the system if the line starts one, such as `system fig1_24a {`.
+/// Productions for a symbol called `system` are not mistaken for the start of a system.
+fn parse_system_start(line: &str) -> crate::Result<Option<&str>> {
+    let rest = match line.strip_prefix("system") {
+        Some(rest) if rest.starts_with(char::is_whitespace) && !rest.contains("->") => rest.trim(),
+        _ => return Ok(None)
+    };
+
+    let name = rest.strip_suffix('{').map(str::trim)
+        .ok_or_else(|| Error::new(ErrorKind::Parse, format!("Expected a brace to start the system: {line}")))?;
+
+    if name.is_empty() || name.contains(|c: char| c.is_whitespace() || c == '{' || c == '}') {
+        return Err(Error::new(ErrorKind::Parse, format!("[{name}] is not a valid system name")));
+    }
+
+    Ok(Some(name))
+}
+
 struct EqualityLine<'a> {
     pub name: &'a str,
     pub value: &'a str
@@ -149,7 +516,7 @@ fn parse_initial(line: &str) -> &str {
     parts[1].trim()
 }
 
-fn parse_equality(line: &str) -> crate::Result<EqualityLine> {
+fn parse_equality(line: &str) -> crate::Result<EqualityLine<'_>> {
     let parts: Vec<&str> = line.splitn(2, '=').collect();
     if parts.len() != 2 {
         return Err(Error::general("Invalid equality line"));
@@ -182,6 +549,125 @@ X -> Forward + [ [ X ] - X ] - Forward [ - Forward X ] + X
 # ENDED";
 
 
+    static NAMED : &str = "delta = 30   # Shared
+n = 3
+
+system first {
+    initial: X
+    X -> Forward X
+}
+
+system second {
+    n = 4
+    delta = 45
+    initial: Forward
+    Forward -> Forward Forward
+}";
+
+    #[test]
+    fn named_systems() {
+        let systems = parse_systems(NAMED).unwrap();
+        assert_eq!(systems.keys().collect::<Vec<_>>(), ["first", "second"]);
+
+        let (interpretation, system, initial) = &systems["first"];
+        assert_eq!(interpretation.n(), 3);
+        assert_eq!(interpretation.delta(), 30.0);
+        assert_eq!(system.production_len(), 1);
+        assert_eq!(initial.to_string(), "X");
+
+        let (interpretation, ..) = parse_system(NAMED, "second").unwrap();
+        assert_eq!(interpretation.n(), 4);
+        assert_eq!(interpretation.delta(), 45.0);
+
+        assert!(parse_system(NAMED, "third").is_err());
+        assert!(parse(NAMED).is_err());
+
+        // A string without named systems has a single, default, system.
+        let systems = parse_systems(GENERAL).unwrap();
+        assert_eq!(systems.keys().collect::<Vec<_>>(), [DEFAULT_SYSTEM]);
+    }
+
+    #[test]
+    fn malformed_named_systems() {
+        assert!(parse_systems("system a {\ninitial: X\nX -> X").is_err());
+        assert!(parse_systems("system a {\ninitial: X\nX -> X\n}\n}").is_err());
+        assert!(parse_systems("system a {\nsystem b {\n}\n}").is_err());
+        assert!(parse_systems("system a\ninitial: X\nX -> X").is_err());
+        assert!(parse_systems("initial: X\nsystem a {\ninitial: X\nX -> X\n}").is_err());
+
+        let twice = "system a {\ninitial: X\nX -> X\n}\nsystem a {\ninitial: X\nX -> X\n}";
+        assert!(parse_systems(twice).is_err());
+
+        let error = parse_systems("system a {\nX -> X\n}").unwrap_err();
+        assert!(error.to_string().contains("System [a]"));
+
+        let (_, system, _) = parse("initial: system\nsystem -> system X").unwrap();
+        assert_eq!(system.production_len(), 1);
+    }
+
+    #[test]
+    fn constants() {
+        let (interpretation, ..) = parse("define ANGLE = 30\ndefine SAME = ANGLE\ndelta = SAME\ninitial: X\nX -> X").unwrap();
+        assert_eq!(interpretation.delta(), 30.0);
+
+        assert!(parse("define ANGLE = 30\ndefine ANGLE = 40\ninitial: X\nX -> X").is_err());
+        assert!(parse("define ANGLE\ninitial: X\nX -> X").is_err());
+        assert!(parse("delta = UNDEFINED\ninitial: X\nX -> X").is_err());
+    }
+
+    #[test]
+    fn includes() {
+        let directory = std::env::temp_dir().join(format!("rusty-systems-includes-{}", std::process::id()));
+        std::fs::create_dir_all(directory.join("shared")).unwrap();
+
+        std::fs::write(directory.join("shared/common.plant"), "define ANGLE = 45\nn = 4\ninclude \"helpers.plant\"").unwrap();
+        std::fs::write(directory.join("shared/helpers.plant"), "Forward -> Forward Forward").unwrap();
+        std::fs::write(directory.join("plant.plant"),
+                       "include \"shared/common.plant\"\ndelta = ANGLE\ninitial: X\nX -> Forward X").unwrap();
+
+        let (interpretation, system, _) = parse_file(directory.join("plant.plant")).unwrap();
+        assert_eq!(interpretation.n(), 4);
+        assert_eq!(interpretation.delta(), 45.0);
+        assert_eq!(system.production_len(), 2);
+
+        std::fs::write(directory.join("first.plant"), "include \"second.plant\"\ninitial: X\nX -> X").unwrap();
+        std::fs::write(directory.join("second.plant"), "include \"first.plant\"").unwrap();
+        let error = parse_file(directory.join("first.plant")).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::Parse));
+        assert!(error.to_string().contains("cycle"));
+
+        assert!(parse("include \"missing.plant\"\ninitial: X\nX -> X").is_err());
+        assert!(parse("include missing.plant\ninitial: X\nX -> X").is_err());
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn turtle_settings() {
+        let plant = "heading = 0\nstep = 2.5\nwidth = 1\nwidth_scale = 0.5\nangle_scale = 2\nlength_scale = 0.7
+initial: X\nX -> Forward ! \" @ X";
+        let (interpretation, ..) = parse(plant).unwrap();
+        assert_eq!(interpretation.heading(), 0.0);
+        assert_eq!(interpretation.step(), 2.5);
+        assert_eq!(interpretation.width(), Some(1.0));
+        assert_eq!(interpretation.width_scale(), 0.5);
+        assert_eq!(interpretation.angle_scale(), 2.0);
+        assert_eq!(interpretation.length_scale(), 0.7);
+
+        let invalid = |setting: &str| {
+            let error = parse(&format!("{setting}\ninitial: X\nX -> X")).unwrap_err();
+            assert!(matches!(error.kind(), ErrorKind::Parse), "{setting}");
+            error.to_string()
+        };
+
+        assert!(invalid("step = -1").contains("positive"));
+        assert!(invalid("width = 0").contains("positive"));
+        assert!(invalid("length_scale = none").contains("[none]"));
+        assert!(invalid("heading = inf").contains("heading"));
+        assert!(invalid("n = 2.5").contains("whole number"));
+        assert!(invalid("colour = red").contains("Unrecognised setting [colour]"));
+    }
+
     #[test]
     fn test_parsing() {
 
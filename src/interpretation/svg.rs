@@ -22,7 +22,7 @@ use std::rc::Rc;
 use crate::error::Error;
 
 use crate::geometry::{Bounds, Path, Point, Vector};
-use crate::prelude::{Interpretation, ProductionString, RunSettings, System};
+use crate::prelude::{Interpretation, ProductionString, RunSettings, Symbol, System};
 use crate::symbols::SymbolStore;
 
 #[derive(Debug, Clone)]
@@ -87,7 +87,30 @@ impl<T> Interpretation for SvgPathInterpretation<T>
     }
 
     fn interpret<S: SymbolStore>(&self, tokens: &S, string: &ProductionString) -> crate::Result<Self::Item> {
-        let paths = self.initial.interpret(tokens, string)?;
+        Ok(self.draw(self.initial.interpret(tokens, string)?))
+    }
+
+    fn interpret_symbols<S, I>(&self, tokens: &S, string: I) -> crate::Result<Self::Item>
+        where S: SymbolStore,
+              I: IntoIterator<Item=Symbol>
+    {
+        Ok(self.draw(self.initial.interpret_symbols(tokens, string)?))
+    }
+
+    fn interpreted_symbols(&self) -> Vec<String> {
+        self.initial.interpreted_symbols()
+    }
+
+    fn run_settings(&self) -> RunSettings {
+        self.initial.run_settings()
+    }
+}
+
+impl<T> SvgPathInterpretation<T>
+    where T: Interpretation<Item=Vec<Path>>
+{
+    /// Scales the paths to fit the canvas.
+    fn draw(&self, paths: Vec<Path>) -> Svg {
         let bounds = paths.bounds();
 
         let center = bounds.as_ref().unwrap().center();
@@ -125,15 +148,11 @@ impl<T> Interpretation for SvgPathInterpretation<T>
             ..SvgGroup::default()
         };
 
-        Ok(Svg {
+        Svg {
             elements: vec![Rc::new(group)],
             width: self.width,
             height: self.height
-        })
-    }
-
-    fn run_settings(&self) -> RunSettings {
-        self.initial.run_settings()
+        }
     }
 }
 
@@ -220,6 +239,9 @@ impl SvgElement for SvgPath {
         if let Some(stroke) = self.stroke() {
             string.push_str(format!(" stroke=\"{}\"", stroke).as_str());
         }
+        if let Some(width) = self.width() {
+            string.push_str(format!(" stroke-width=\"{}\"", width).as_str());
+        }
 
         if !self.is_empty() {
             string.push_str(" d=\"");
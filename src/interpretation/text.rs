@@ -0,0 +1,261 @@
+//! Interprets strings as text, for generating words, names and other phrases.
+//!
+//! [`TextInterpretation`] maps symbols to fragments of text, and joins the fragments
+//! together. A symbol may have several alternative fragments, one of which is chosen at random
+//! each time the symbol occurs. Giving a seed makes these choices repeatable.
+//!
+//! ```
+//! use rusty_systems::prelude::*;
+//! use rusty_systems::interpretation::text::{Capitalisation, TextInterpretation};
+//!
+//! let system = System::new();
+//! system.add_production("Company -> surname and surname").unwrap();
+//!
+//! let string = system.derive_once("Company".parse().unwrap()).unwrap();
+//!
+//! let text = TextInterpretation::new()
+//!     .with_alternatives("surname".parse().unwrap(), &["smith", "jones"])
+//!     .with_fragment("and".parse().unwrap(), "&")
+//!     .with_capitalisation(Capitalisation::Words)
+//!     .with_seed(42);
+//!
+//! let name = text.interpret(&system, &string).unwrap();
+//! assert!(name.ends_with("Smith") || name.ends_with("Jones"));
+//! assert_eq!(name, text.interpret(&system, &string).unwrap());   // The seed makes this repeatable
+//! ```
+//!
+//! Fragments can also come from the descriptions of a [`SystemFamily`]'s terminals.
+//! See [`TextInterpretation::from_family`].
+
+use std::collections::HashMap;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{thread_rng, RngCore, SeedableRng};
+
+use crate::interpretation::Interpretation;
+use crate::prelude::*;
+use crate::symbols::SymbolStore;
+
+/// How [`TextInterpretation`] capitalises the text it produces.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum Capitalisation {
+    /// The fragments are used as they are.
+    #[default]
+    None,
+    /// The first letter of the text is capitalised.
+    First,
+    /// The first letter of every word is capitalised. Each fragment is a word, as is each part of a
+    /// fragment separated by spaces.
+    Words
+}
+
+/// Interprets strings as text. See the [module documentation](crate::interpretation::text).
+///
+/// Symbols without a fragment are written using their own name. Map a symbol to
+/// the empty string to leave it out of the text.
+#[derive(Debug, Clone)]
+pub struct TextInterpretation {
+    fragments: HashMap<Symbol, Vec<String>>,
+    separator: String,
+    capitalisation: Capitalisation,
+    seed: Option<u64>
+}
+
+impl Default for TextInterpretation {
+    fn default() -> Self {
+        TextInterpretation {
+            fragments: HashMap::new(),
+            separator: String::from(" "),
+            capitalisation: Capitalisation::default(),
+            seed: None
+        }
+    }
+}
+
+impl TextInterpretation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an interpretation whose fragments are the descriptions of the family's terminals.
+    /// Alternatives are separated by `|`, so that a terminal described as `Smith|Jones` is written
+    /// as either `Smith` or `Jones`. Terminals without a description are written using their name.
+    ///
+    /// ```
+    /// use rusty_systems::prelude::*;
+    /// use rusty_systems::interpretation::text::TextInterpretation;
+    ///
+    /// let family = SystemFamily::define()
+    ///     .with_production("Greeting", None)
+    ///     .with_terminal("hello", Some("hello|hi"))
+    ///     .with_terminal("world", None)
+    ///     .build("Greetings")
+    ///     .unwrap();
+    ///
+    /// let text = TextInterpretation::from_family(&family).unwrap();
+    /// let system = System::of_family(family).unwrap();
+    /// let greeting = text.interpret(&system, &"hello world".parse().unwrap()).unwrap();
+    /// assert!(greeting == "hello world" || greeting == "hi world");
+    /// ```
+    pub fn from_family(family: &SystemFamily) -> crate::Result<Self> {
+        let mut result = Self::default();
+
+        for terminal in family.terminals() {
+            if let Some(description) = &terminal.description {
+                let alternatives: Vec<&str> = description.split('|').map(str::trim).collect();
+                result = result.with_alternatives(Symbol::build(&terminal.name)?, &alternatives);
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Returns this interpretation writing `symbol` as the given text.
+    pub fn with_fragment<S: AsRef<str>>(self, symbol: Symbol, fragment: S) -> Self {
+        self.with_alternatives(symbol, &[fragment])
+    }
+
+    /// Returns this interpretation writing `symbol` as one of the given alternatives, chosen
+    /// at random each time the symbol occurs.
+    pub fn with_alternatives<S: AsRef<str>>(mut self, symbol: Symbol, alternatives: &[S]) -> Self {
+        let alternatives = alternatives.iter().map(|s| s.as_ref().to_string()).collect();
+        self.fragments.insert(symbol, alternatives);
+        self
+    }
+
+    /// Returns this interpretation joining fragments with the given separator. This defaults
+    /// to a single space.
+    pub fn with_separator<S: AsRef<str>>(mut self, separator: S) -> Self {
+        self.separator = separator.as_ref().to_string();
+        self
+    }
+
+    /// Returns this interpretation capitalising text as given. See [`Capitalisation`].
+    pub fn with_capitalisation(mut self, capitalisation: Capitalisation) -> Self {
+        self.capitalisation = capitalisation;
+        self
+    }
+
+    /// Returns this interpretation choosing between alternatives using the given seed, so that
+    /// interpreting the same string always produces the same text.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// The alternative fragments that `symbol` may be written as, if it has any.
+    pub fn fragments(&self, symbol: Symbol) -> Option<&[String]> {
+        self.fragments.get(&symbol).map(|f| f.as_slice())
+    }
+}
+
+impl Interpretation for TextInterpretation {
+    type Item = String;
+
+    fn system() -> crate::Result<System> {
+        Ok(System::new())
+    }
+
+    fn interpret<S: SymbolStore>(&self, _: &S, string: &ProductionString) -> crate::Result<Self::Item> {
+        let mut rng: Box<dyn RngCore> = match self.seed {
+            Some(seed) => Box::new(StdRng::seed_from_u64(seed)),
+            None => Box::new(thread_rng())
+        };
+
+        let mut words: Vec<String> = Vec::with_capacity(string.len());
+        for symbol in string {
+            let fragment = match self.fragments.get(&symbol) {
+                None => symbol.to_string(),
+                Some(alternatives) => alternatives.choose(&mut rng).cloned().unwrap_or_default()
+            };
+
+            if fragment.is_empty() {
+                continue;
+            }
+
+            match self.capitalisation {
+                Capitalisation::Words => words.push(fragment.split(' ').map(capitalise).collect::<Vec<_>>().join(" ")),
+                _ => words.push(fragment)
+            }
+        }
+
+        let text = words.join(&self.separator);
+
+        Ok(match self.capitalisation {
+            Capitalisation::First => capitalise(&text),
+            _ => text
+        })
+    }
+
+    fn interpreted_symbols(&self) -> Vec<String> {
+        self.fragments.keys().map(|s| s.to_string()).collect()
+    }
+}
+
+fn capitalise(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        None => String::new(),
+        Some(first) => first.to_uppercase().chain(chars).collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_prod_string;
+
+    fn symbol(name: &str) -> Symbol {
+        Symbol::build(name).unwrap()
+    }
+
+    #[test]
+    fn joins_and_capitalises() {
+        let system = System::new();
+        let string = parse_prod_string("first hidden second").unwrap();
+
+        let text = TextInterpretation::new()
+            .with_fragment(symbol("first"), "ada")
+            .with_fragment(symbol("hidden"), "")
+            .with_fragment(symbol("second"), "lovelace");
+        assert_eq!(text.interpret(&system, &string).unwrap(), "ada lovelace");
+
+        let text = text.with_separator("-").with_capitalisation(Capitalisation::First);
+        assert_eq!(text.interpret(&system, &string).unwrap(), "Ada-lovelace");
+
+        let text = text.with_separator(" ").with_capitalisation(Capitalisation::Words);
+        assert_eq!(text.interpret(&system, &string).unwrap(), "Ada Lovelace");
+
+        // Unmapped symbols use their names
+        let string = parse_prod_string("first unknown").unwrap();
+        assert_eq!(text.interpret(&system, &string).unwrap(), "Ada Unknown");
+
+        // Every fragment is capitalised, whatever the separator.
+        let string = parse_prod_string("first second").unwrap();
+        let text = text.with_separator("-");
+        assert_eq!(text.interpret(&system, &string).unwrap(), "Ada-Lovelace");
+
+        let text = text.with_separator("").with_fragment(symbol("second"), "countess of lovelace");
+        assert_eq!(text.interpret(&system, &string).unwrap(), "AdaCountess Of Lovelace");
+    }
+
+    #[test]
+    fn seeds_are_repeatable() {
+        let system = System::new();
+        system.add_production("Name -> syllable syllable syllable syllable").unwrap();
+        let string = system.derive_once(parse_prod_string("Name").unwrap()).unwrap();
+
+        let text = TextInterpretation::new()
+            .with_alternatives(symbol("syllable"), &["ka", "lo", "mi", "ne", "ru"])
+            .with_separator("")
+            .with_seed(7);
+
+        let first = text.interpret(&system, &string).unwrap();
+        assert_eq!(first.len(), 8);
+        for _ in 0..5 {
+            assert_eq!(text.interpret(&system, &string).unwrap(), first);
+        }
+    }
+}
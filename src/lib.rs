@@ -105,6 +105,7 @@ pub mod system;
 pub mod geometry;
 pub mod interpretation;
 pub mod parser;
+pub mod analysis;
 
 /// Some commonly used members of the crate re-exported for easy access.
 /// 
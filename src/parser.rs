@@ -17,7 +17,9 @@ use crate::parser::statement::ParsableType;
 
 pub mod iterator;
 pub mod token;
+#[allow(dead_code)]
 mod statement;
+#[allow(dead_code)]
 mod parsestack;
 
 /// Parse the body of a production rule.
@@ -53,6 +55,12 @@ pub fn parse_production_body(body: &str) -> Result<ProductionBody> {
 }
 
 /// Parse the head of a production rule.
+///
+/// A head with more than one symbol before any right context, such as `A B -> C`, has a
+/// sequence of symbols as its predecessor. See [`ProductionHead::build_sequence`].
+///
+/// The contexts may contain the wildcard, `*`, and references to symbol classes, such as `{Leaf}`.
+/// See the [productions module](crate::productions#patterns-in-contexts).
 pub fn parse_production_head(head: &str) -> Result<ProductionHead> {
     let head = head.trim();
 
@@ -82,12 +90,13 @@ pub fn parse_production_head(head: &str) -> Result<ProductionHead> {
         split[0]
     };
 
-    if remains.len() != 1 {
-        return Err(Error::new(ErrorKind::Parse, "There should be exactly one token as the head target"))
+    if remains.is_empty() {
+        return Err(Error::new(ErrorKind::Parse, "There should be at least one token as the head target"))
     }
 
-    let center = remains[0];
-    let head_token = Symbol::build(center)?;
+    let targets = remains.iter()
+        .map(Symbol::build)
+        .collect::<Result<Vec<_>>>()?;
 
     let left = parse_head_context(left);
     if let Some(Err(e)) = left {
@@ -103,10 +112,10 @@ pub fn parse_production_head(head: &str) -> Result<ProductionHead> {
 
     let right = right.map(|d| d.unwrap());
 
-    ProductionHead::build(
-        left,
-        head_token,
-        right)
+    match targets.len() {
+        1 => ProductionHead::build(left, targets[0], right),
+        _ => ProductionHead::build_sequence(left, ProductionString::from(targets), right)
+    }
 }
 
 fn parse_head_context(strings: Option<&[&str]>) -> Option<Result<ProductionString>> {
@@ -152,6 +161,17 @@ fn parse_head_context(strings: Option<&[&str]>) -> Option<Result<ProductionStrin
 /// system.add_production("Company -> Surname And Surname").unwrap();
 /// ```
 /// 
+/// A [priority](Production::priority) may be given in parentheses directly after the arrow.
+/// It defaults to zero, and decides between productions that would otherwise be ambiguous:
+///
+/// ```
+/// use rusty_systems::parser;
+///
+/// let production = parser::parse_production("A < X ->(2) F").unwrap();
+/// assert_eq!(production.priority(), 2);
+/// assert_eq!(production.to_string(), "A < X ->(2) F");
+/// ```
+///
 /// See also:
 /// * [`parse_and_add_production`]
 pub fn parse_production(production: &str) -> Result<Production> {
@@ -167,12 +187,28 @@ pub fn parse_production(production: &str) -> Result<Production> {
                                   String::from("supplied string is not a production: ") + production))?;
 
     let head_str = &production[0..index];
-    let body_str = &production[index + 2..];
+    let (priority, body_str) = parse_priority(&production[index + 2..])?;
 
     let head = parse_production_head(head_str)?;
     let body = parse_production_body(body_str)?;
 
-    Ok(Production::new(head, body))
+    Ok(Production::new(head, body).with_priority(priority))
+}
+
+/// Parses the priority that may directly follow a production's arrow, such as the `(2)`
+/// in `X ->(2) F`. Returns the priority and the remaining text.
+fn parse_priority(after_arrow: &str) -> Result<(i32, &str)> {
+    let Some(rest) = after_arrow.strip_prefix('(') else {
+        return Ok((0, after_arrow));
+    };
+
+    let end = rest.find(')')
+        .ok_or_else(|| Error::new(ErrorKind::Parse, "the priority after the arrow is missing a closing parenthesis"))?;
+
+    let priority = rest[..end].trim().parse()
+        .map_err(|_| Error::new(ErrorKind::Parse, format!("the priority ({}) should be a whole number", &rest[..end])))?;
+
+    Ok((priority, &rest[end + 1..]))
 }
 
 
@@ -284,6 +320,43 @@ mod test {
         assert_eq!(get_code("Post").unwrap(), right[0].code());
     }
 
+    #[test]
+    fn parsing_sequence_heads() {
+        let head = parse_production_head("Pre < A B > Post").unwrap();
+        assert!(head.is_sequence());
+        assert_eq!(head.predecessor(), parse_prod_string("A B").unwrap());
+        assert_eq!(head.post_context().unwrap().len(), 1);
+        assert_eq!(head.to_string(), "Pre < A B > Post");
+
+        assert!(!parse_production_head("A").unwrap().is_sequence());
+        assert!(parse_production_head("Pre < > Post").is_err());
+    }
+
+    #[test]
+    fn parsing_context_patterns() {
+        let head = parse_production_head("* {Leaf} < A > *").unwrap();
+        let left = head.pre_context().unwrap();
+        assert_eq!(left[0], crate::productions::wildcard());
+        assert_eq!(left[1].to_string(), "{Leaf}");
+
+        assert!(parse_production_head("*").is_err());
+        assert!(parse_production_head("A *").is_err());
+        assert!(parse_production_head("A < {Leaf}").is_err());
+    }
+
+    #[test]
+    fn parsing_priorities() {
+        assert_eq!(parse_production("X -> F").unwrap().priority(), 0);
+        assert_eq!(parse_production("A < X ->(3) F F").unwrap().priority(), 3);
+        assert_eq!(parse_production("X ->( -2 )").unwrap().priority(), -2);
+
+        let production = parse_production("X ->(1) 0.5 F").unwrap();
+        assert_eq!(production.all_bodies()[0].to_string(), "0.5 F");
+
+        assert!(matches!(parse_production("X ->(high) F").unwrap_err().kind(), ErrorKind::Parse));
+        assert!(matches!(parse_production("X ->(1 F").unwrap_err().kind(), ErrorKind::Parse));
+    }
+
     #[test]
     fn parsing_strings() {
         let s = parse_prod_string("A B C").unwrap();
@@ -7,12 +7,44 @@
 //! let production: Production = "X -> F F F".parse().expect("Unable to parse");
 //! ```
 //! 
+//! # Patterns in contexts
+//!
+//! The contexts of a production may contain patterns as well as symbols:
+//!
+//! * The wildcard, `*` (see [`WILDCARD`]), matches any single symbol.
+//! * A symbol class, written as its name in braces, such as `{Leaf}`, matches any of the
+//!   class's members. Classes are declared by a [`SystemFamily`] (see
+//!   [`Builder::with_class`](crate::system::family::Builder::with_class)), and are looked up
+//!   when the production is added to a [`System`] of that family.
+//!
+//! ```
+//! use rusty_systems::prelude::*;
+//!
+//! let family = SystemFamily::define()
+//!     .with_production("X", None)
+//!     .with_terminal("L1", None)
+//!     .with_terminal("L2", None)
+//!     .with_terminal("Y", None)
+//!     .with_class("Leaf", &["L1", "L2"])
+//!     .build("Leaves")
+//!     .unwrap();
+//!
+//! let system = System::of_family(family).unwrap();
+//! system.add_production("{Leaf} < X > * -> Y").unwrap();
+//!
+//! let string = system.derive_once("L2 X L1 X".parse().unwrap()).unwrap();
+//! assert_eq!(string.to_string(), "L2 Y L1 X");
+//! ```
+//!
 //! todo add more detail
 
 use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
 use std::hash::{Hash, Hasher};
 use std::str::FromStr;
-use rand::{Rng, thread_rng};
+use std::sync::OnceLock;
+use rand::{Rng, RngCore, thread_rng};
 
 use crate::{parser, Result};
 use crate::error::{Error, ErrorKind};
@@ -85,29 +117,137 @@ impl Chance {
 }
 
 
+/// The name of the wildcard symbol, which matches any single symbol in a production's context.
+pub const WILDCARD: &str = "*";
+
+/// Returns the wildcard symbol. See [`WILDCARD`].
+pub fn wildcard() -> Symbol {
+    static SYMBOL: OnceLock<Symbol> = OnceLock::new();
+    *SYMBOL.get_or_init(|| Symbol::build(WILDCARD).expect("the wildcard is a valid symbol name"))
+}
+
+/// Returns the name of the class that a symbol name refers to, if the name is
+/// a reference to a symbol class, such as `{Leaf}`.
+pub fn class_name(name: &str) -> Option<&str> {
+    name.strip_prefix('{')
+        .and_then(|name| name.strip_suffix('}'))
+        .filter(|name| !name.is_empty())
+}
+
+/// What a single symbol in a production's context will match.
+enum Pattern<'a> {
+    Any,
+    Class(&'a [Symbol]),
+    Exactly(Symbol)
+}
+
+impl Pattern<'_> {
+    fn matches(&self, symbol: Symbol) -> bool {
+        match self {
+            Pattern::Any => true,
+            Pattern::Class(members) => members.contains(&symbol),
+            Pattern::Exactly(exact) => *exact == symbol
+        }
+    }
+
+    fn overlaps(&self, other: &Pattern) -> bool {
+        match (self, other) {
+            (Pattern::Any, _) | (_, Pattern::Any) => true,
+            (Pattern::Exactly(symbol), other) | (other, Pattern::Exactly(symbol)) => other.matches(*symbol),
+            (Pattern::Class(members), other) => members.iter().any(|s| other.matches(*s))
+        }
+    }
+
+    fn subsumes(&self, other: &Pattern) -> bool {
+        match (self, other) {
+            (Pattern::Any, _) => true,
+            (_, Pattern::Any) => false,
+            (_, Pattern::Exactly(symbol)) => self.matches(*symbol),
+            (_, Pattern::Class(members)) => members.iter().all(|s| self.matches(*s))
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ProductionHead {
     pre: Option<ProductionString>,
     target: Symbol,
-    post: Option<ProductionString>
+    /// The symbols following the target, for heads that match a sequence of symbols.
+    tail: Vec<Symbol>,
+    post: Option<ProductionString>,
+    /// The members of each symbol class referred to in the contexts.
+    classes: Vec<(Symbol, Vec<Symbol>)>
 }
 
 impl ProductionHead {
     /// Create a new production head.
+    ///
+    /// The target must be a symbol, and not a [wildcard](WILDCARD) or a class reference.
     pub fn build(pre: Option<ProductionString>, target: Symbol, post: Option<ProductionString>) -> Result<Self> {
+        if target == wildcard() || class_name(&target.to_string()).is_some() {
+            return Err(Error::new(ErrorKind::Parse,
+                                  format!("the target of a production must be a symbol, not the pattern [{target}]")));
+        }
+
         Ok(ProductionHead {
             pre,
             target,
-            post
+            tail: Vec::new(),
+            post,
+            classes: Vec::new()
         })
     }
 
-    /// Returns the symbol that this production is a target of.
+    /// Create a production head whose predecessor is a sequence of symbols, such as
+    /// the `A B` of `A B -> C`.
+    ///
+    /// Such heads are only accepted by a [`System`] that allows them.
+    /// See [`System::with_sequence_heads`].
+    pub fn build_sequence(pre: Option<ProductionString>, predecessor: ProductionString, post: Option<ProductionString>) -> Result<Self> {
+        let mut symbols = predecessor.into_iter();
+        let target = symbols.next()
+            .ok_or_else(|| Error::new(ErrorKind::Parse, "the predecessor of a production cannot be empty"))?;
+
+        let mut head = Self::build(pre, target, post)?;
+        for symbol in symbols {
+            if symbol == wildcard() || class_name(&symbol.to_string()).is_some() {
+                return Err(Error::new(ErrorKind::Parse,
+                                      format!("the predecessor of a production must be symbols, not the pattern [{symbol}]")));
+            }
+
+            head.tail.push(symbol);
+        }
+
+        Ok(head)
+    }
+
+    /// Returns the symbol that this production is a target of. For heads with a
+    /// sequence of symbols as their predecessor, this is the first of them.
     #[inline]
     pub fn target(&self) -> &Symbol {
         &self.target
     }
 
+    /// Returns the sequence of symbols that this head rewrites. This is only longer than
+    /// the [target](ProductionHead::target) for heads created with [`ProductionHead::build_sequence`].
+    pub fn predecessor(&self) -> ProductionString {
+        let mut result = ProductionString::from(self.target);
+        self.tail.iter().for_each(|s| result.push_symbol(*s));
+        result
+    }
+
+    /// The number of symbols that this head rewrites.
+    #[inline]
+    pub fn predecessor_len(&self) -> usize {
+        1 + self.tail.len()
+    }
+
+    /// Returns true iff this head rewrites a sequence of more than one symbol.
+    #[inline]
+    pub fn is_sequence(&self) -> bool {
+        !self.tail.is_empty()
+    }
+
     #[inline]
     pub fn pre_context(&self) -> Option<&ProductionString> {
         self.pre.as_ref()
@@ -118,14 +258,149 @@ impl ProductionHead {
         self.post.as_ref()
     }
 
+    /// Returns every symbol class referred to in the contexts, such as `{Leaf}`, whether or not
+    /// its members have been defined.
+    pub fn class_references(&self) -> Vec<Symbol> {
+        let mut result: Vec<Symbol> = Vec::new();
+        for symbol in self.pre.iter().chain(self.post.iter()).flat_map(|s| s.iter()) {
+            if !result.contains(symbol) && class_name(&symbol.to_string()).is_some() {
+                result.push(*symbol);
+            }
+        }
+
+        result
+    }
+
+    /// Defines the members of a symbol class that the contexts refer to.
+    ///
+    /// [`System::add_production`](crate::productions::ProductionStore::add_production) does this
+    /// using the classes declared by the system's family. References to classes that have not been
+    /// defined only match the reference itself.
+    pub fn define_class(&mut self, reference: Symbol, members: Vec<Symbol>) {
+        match self.classes.iter_mut().find(|(r, _)| *r == reference) {
+            Some((_, existing)) => *existing = members,
+            None => self.classes.push((reference, members))
+        }
+    }
+
+    /// The members of the given class, if they have been defined. See [`ProductionHead::define_class`].
+    pub fn class_members(&self, reference: Symbol) -> Option<&[Symbol]> {
+        self.classes.iter()
+            .find(|(r, _)| *r == reference)
+            .map(|(_, members)| members.as_slice())
+    }
+
+    /// Returns true iff the symbol is the wildcard, or a class reference that has been
+    /// defined for this head.
+    pub fn is_pattern(&self, symbol: Symbol) -> bool {
+        symbol == wildcard() || self.class_members(symbol).is_some()
+    }
+
+    /// Returns true iff `symbol` would be matched by the given symbol, or pattern,
+    /// of this head's contexts.
+    #[inline]
+    pub fn context_matches(&self, pattern: Symbol, symbol: Symbol) -> bool {
+        self.pattern(pattern).matches(symbol)
+    }
+
+    fn pattern(&self, symbol: Symbol) -> Pattern<'_> {
+        if symbol == wildcard() {
+            return Pattern::Any;
+        }
+
+        match self.class_members(symbol) {
+            Some(members) => Pattern::Class(members),
+            None => Pattern::Exactly(symbol)
+        }
+    }
+
+    /// How specific this head is, being the total number of symbols in its
+    /// left and right contexts. Context-free heads have a specificity of zero.
+    /// Wildcards and classes count as much as any other symbol. Each symbol after the first
+    /// in a [sequence](ProductionHead::build_sequence) also counts, so that longer sequences
+    /// are preferred to shorter ones.
+    ///
+    /// When more than one production matches a symbol, the more specific production
+    /// is preferred. See [`find_matching`](crate::system::find_matching).
+    pub fn specificity(&self) -> usize {
+        self.pre.as_ref().map(|p| p.len()).unwrap_or(0) +
+            self.tail.len() +
+            self.post.as_ref().map(|p| p.len()).unwrap_or(0)
+    }
+
+    /// The patterns that must follow the target: the rest of the predecessor, and then
+    /// the right context.
+    fn right_patterns(&self) -> Vec<Pattern<'_>> {
+        self.tail.iter()
+            .map(|s| Pattern::Exactly(*s))
+            .chain(self.post.iter().flat_map(|p| p.iter()).map(|s| self.pattern(*s)))
+            .collect()
+    }
+
+    /// Returns true iff there is some string and position that both this head and
+    /// `other` would match.
+    ///
+    /// Two heads overlap when they have the same target, and their contexts do not
+    /// contradict one another: the shorter left context must be a suffix of the longer one,
+    /// and the shorter right context must be a prefix of the longer one. Patterns agree with
+    /// any symbol or pattern that they have a match in common with. The symbols following
+    /// the target of a sequence are treated as part of its right context.
+    pub fn overlaps(&self, other: &ProductionHead) -> bool {
+        if self.target != other.target {
+            return false;
+        }
+
+        let empty = ProductionString::empty();
+        let left = self.pre.as_ref().unwrap_or(&empty);
+        let other_left = other.pre.as_ref().unwrap_or(&empty);
+
+        let left_agrees = left.symbols().iter().rev()
+            .zip(other_left.symbols().iter().rev())
+            .all(|(a, b)| self.pattern(*a).overlaps(&other.pattern(*b)));
+        let right_agrees = self.right_patterns().iter()
+            .zip(other.right_patterns().iter())
+            .all(|(a, b)| a.overlaps(b));
+
+        left_agrees && right_agrees
+    }
+
+    /// Returns true iff this head matches every string position that `other` matches.
+    ///
+    /// This is the case when both have the same target, and this head's contexts
+    /// are a less restrictive version of `other`'s: its left context is a suffix of the
+    /// other's left context, and its right context is a prefix of the other's right context.
+    /// A pattern subsumes the symbols and patterns whose matches it also matches. As with
+    /// [`ProductionHead::overlaps`], the rest of a sequence is treated as part of the right context.
+    pub fn subsumes(&self, other: &ProductionHead) -> bool {
+        if self.target != other.target {
+            return false;
+        }
+
+        let empty = ProductionString::empty();
+        let left = self.pre.as_ref().unwrap_or(&empty);
+        let other_left = other.pre.as_ref().unwrap_or(&empty);
+        let right = self.right_patterns();
+        let other_right = other.right_patterns();
+
+        left.len() <= other_left.len() &&
+            right.len() <= other_right.len() &&
+            left.symbols().iter().rev()
+                .zip(other_left.symbols().iter().rev())
+                .all(|(a, b)| self.pattern(*a).subsumes(&other.pattern(*b))) &&
+            right.iter()
+                .zip(other_right.iter())
+                .all(|(a, b)| a.subsumes(b))
+    }
+
     /// Returns true iff this matches the given
-    /// string's index position of the string.
+    /// string's index position of the string. Heads with a sequence as their predecessor
+    /// match if the sequence starts at `index`.
     pub fn matches(&self, string: &ProductionString, index: usize) -> bool {
         self.pre_matches(string, index) &&
         self.post_matches(string, index) &&
             string.symbols()
-                .get(index)
-                .map(|symbol| self.target == *symbol)
+                .get(index..index + self.predecessor_len())
+                .map(|symbols| self.target == symbols[0] && self.tail == symbols[1..])
                 .unwrap_or(false)
     }
 
@@ -136,16 +411,14 @@ impl ProductionHead {
 
         let left = self.pre.as_ref().unwrap();
 
-        if index == 0 {
-            return left.is_empty();
-        }
-
-        let symbols: Vec<_> = string.symbols()[0..index].iter().rev().collect();
+        let symbols = string.symbols().get(..index).unwrap_or(&[]);
         if symbols.len() < left.len() {
             return false;
         }
 
-        return left.iter().rev().enumerate().all(|(i, t)| t == symbols[i]);
+        left.iter().rev()
+            .zip(symbols.iter().rev())
+            .all(|(t, s)| self.context_matches(*t, *s))
     }
 
     pub fn post_matches(&self, string: &ProductionString, index: usize) -> bool {
@@ -155,16 +428,12 @@ impl ProductionHead {
 
         let right = self.post.as_ref().unwrap();
 
-        if index == string.len() - 1 {
-            return right.is_empty();
-        }
-
-        let symbols = string.symbols()[index + 1 ..].to_vec();
+        let symbols = string.symbols().get(index + self.predecessor_len()..).unwrap_or(&[]);
         if symbols.len() < right.len() {
             return false;
         }
 
-        return right.iter().enumerate().all(|(i, t)| *t == symbols[i]);
+        right.iter().zip(symbols).all(|(t, s)| self.context_matches(*t, *s))
     }
 
 }
@@ -238,6 +507,11 @@ impl ProductionBody {
 /// the arrow (in this case the `B`, called here the [`ProductionBody`] is what
 /// the symbols matching the head in the input string / axiom will be replaced with.
 ///
+/// When more than one production matches the same symbol, the production with the
+/// highest [`Production::priority`] is used. Productions of equal priority prefer
+/// the one with the longest context (see [`ProductionHead::specificity`]), so that
+/// context-sensitive productions take precedence over context-free ones, as in ABOP.
+///
 /// See:
 /// * [`Production::head`]
 /// * [`Production::body`]
@@ -245,14 +519,16 @@ impl ProductionBody {
 #[derive(Debug, Clone)]
 pub struct Production {
     head: ProductionHead,
-    body: Vec<ProductionBody>
+    body: Vec<ProductionBody>,
+    priority: i32
 }
 
 impl Production {
     pub fn new(head: ProductionHead, body: ProductionBody) -> Self {
         Production {
             head,
-            body: vec![body]
+            body: vec![body],
+            priority: 0
         }
     }
 
@@ -261,14 +537,90 @@ impl Production {
         &self.head
     }
 
+    /// Defines the members of a symbol class referred to by this production's head.
+    /// See [`ProductionHead::define_class`].
+    #[inline]
+    pub fn define_class(&mut self, reference: Symbol, members: Vec<Symbol>) {
+        self.head.define_class(reference, members);
+    }
+
+    /// The explicit priority of this production. This defaults to zero.
+    ///
+    /// Higher priorities win over lower ones when more than one production matches
+    /// the same symbol, regardless of how long their contexts are. When parsing, a priority
+    /// is given in parentheses after the arrow, as in `X ->(2) F`. See [`parse_production`](crate::parser::parse_production).
+    #[inline]
+    pub fn priority(&self) -> i32 {
+        self.priority
+    }
+
+    /// Sets the explicit priority of this production. See [`Production::priority`].
+    #[inline]
+    pub fn set_priority(&mut self, priority: i32) {
+        self.priority = priority;
+    }
+
+    /// Returns this production with the given priority. See [`Production::priority`].
+    ///
+    /// ```
+    /// use rusty_systems::productions::Production;
+    /// let production = "X -> F".parse::<Production>().unwrap().with_priority(10);
+    /// assert_eq!(production.priority(), 10);
+    /// ```
+    #[inline]
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Orders productions by precedence: first by [`Production::priority`], and then by
+    /// [`ProductionHead::specificity`]. A production that is [`Ordering::Greater`]
+    /// than another takes precedence over it.
+    pub fn precedence_cmp(&self, other: &Production) -> Ordering {
+        self.priority.cmp(&other.priority)
+            .then_with(|| self.head.specificity().cmp(&other.head.specificity()))
+    }
+
+    /// Returns true iff this production and `other` could both match the same
+    /// symbol with the same precedence, in which case which is applied only depends on the
+    /// order in which they were added.
+    ///
+    /// See [`ProductionHead::overlaps`] and [`Production::precedence_cmp`].
+    pub fn is_ambiguous_with(&self, other: &Production) -> bool {
+        self.head != other.head &&
+            self.precedence_cmp(other).is_eq() &&
+            self.head.overlaps(&other.head)
+    }
+
     pub fn body(&self) -> Result<&ProductionBody> {
+        self.body_with(&mut thread_rng())
+    }
+
+    /// Chooses one of the production's bodies, as [`Production::body`] does, using the given
+    /// random number generator.
+    pub fn body_with(&self, rng: &mut dyn RngCore) -> Result<&ProductionBody> {
+        Ok(&self.body[self.choose_body_index_with(rng)?])
+    }
+
+    /// Chooses one of the production's bodies, as [`Production::body`] does, and returns its
+    /// index in [`Production::all_bodies`].
+    ///
+    /// This returns an [`ErrorKind::Execution`](crate::error::ErrorKind::Execution) error if
+    /// the production has no bodies, or if the chances of its bodies are invalid.
+    pub fn choose_body_index(&self) -> Result<usize> {
+        self.choose_body_index_with(&mut thread_rng())
+    }
+
+    /// Chooses the index of one of the production's bodies, as [`Production::choose_body_index`]
+    /// does, using the given random number generator.
+    pub fn choose_body_index_with(&self, rng: &mut dyn RngCore) -> Result<usize> {
         if self.body.is_empty() {
             return Err(Error::execution("Production has no bodies set"))
         }
 
         // Return the only instance. Chance does not matter here.
         if self.body.len() == 1 {
-            return Ok(self.body.last().unwrap());
+            return Ok(0);
         }
 
         let total_chance : f32 = self.body.iter()
@@ -291,17 +643,17 @@ impl Production {
         };
 
         let mut current = 0_f32;
-        let random : f32 = thread_rng().gen_range(0.0..=1.0);
+        let random : f32 = rng.gen_range(0.0..=1.0);
 
-        for body in &self.body {
+        for (index, body) in self.body.iter().enumerate() {
             current += body.chance.unwrap_or(default_chance);
             if random < current {
-                return Ok(body);
+                return Ok(index);
             }
         }
 
         // All remaining chance given to last body.
-        return Ok(self.body.last().unwrap());
+        Ok(self.body.len() - 1)
     }
 
     /// Returns true iff this production's [`Production::head`] matches the given
@@ -316,7 +668,10 @@ impl Production {
     }
 
     /// Adds all of the body elements from `other` into `self`.
+    ///
+    /// The merged production keeps the higher of the two priorities.
     pub fn merge(&mut self, other: Self) {
+        self.priority = self.priority.max(other.priority);
         other.body.into_iter().for_each(|b| self.add_body(b));
     }
 
@@ -326,6 +681,57 @@ impl Production {
     }
 }
 
+impl Display for ProductionHead {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(pre) = &self.pre {
+            write!(f, "{pre} < ")?;
+        }
+
+        write!(f, "{}", self.target)?;
+        for symbol in &self.tail {
+            write!(f, " {symbol}")?;
+        }
+
+        if let Some(post) = &self.post {
+            write!(f, " > {post}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for ProductionBody {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.chance.is_user_set() {
+            write!(f, "{} ", self.chance.unwrap())?;
+        }
+
+        write!(f, "{}", self.string)
+    }
+}
+
+/// Productions with more than one body are displayed as one production per body,
+/// separated by newlines.
+impl Display for Production {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut first = true;
+        for body in &self.body {
+            if !first {
+                writeln!(f)?;
+            } else {
+                first = false;
+            }
+
+            match self.priority {
+                0 => write!(f, "{} -> {}", self.head, body)?,
+                priority => write!(f, "{} ->({priority}) {}", self.head, body)?
+            }
+        }
+
+        Ok(())
+    }
+}
+
 impl PartialEq for Production {
     fn eq(&self, other: &Self) -> bool {
         self.head().eq(other.head())
@@ -447,4 +853,88 @@ mod tests {
         assert!( production.matches(&string, 1));
         assert!(!production.matches(&string, 2));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn heads_overlap() {
+        let head = |s: &str| parser::parse_production_head(s).unwrap();
+
+        assert!( head("X").overlaps(&head("A < X > B")));
+        assert!( head("B < X").overlaps(&head("A B < X")));
+        assert!(!head("A < X").overlaps(&head("A B < X")));
+        assert!( head("X > A").overlaps(&head("X > A B")));
+        assert!(!head("X > B").overlaps(&head("X > A B")));
+        assert!(!head("X").overlaps(&head("Y")));
+
+        assert!( head("X").subsumes(&head("A < X > B")));
+        assert!( head("B < X").subsumes(&head("A B < X")));
+        assert!(!head("A B < X").subsumes(&head("B < X")));
+        assert!(!head("X > B").subsumes(&head("X > A B")));
+
+        assert_eq!(head("X").specificity(), 0);
+        assert_eq!(head("A B < X > C").specificity(), 3);
+    }
+
+    #[test]
+    fn context_patterns() {
+        let leaves = || ["L1", "L2"].map(|s| Symbol::build(s).unwrap()).to_vec();
+        let mut production: Production = "{Leaf} < X > * -> Y".parse().unwrap();
+        let class = Symbol::build("{Leaf}").unwrap();
+        assert_eq!(production.head().class_references(), [class]);
+
+        let string = parse_prod_string("L2 X L1 X").unwrap();
+        // Undefined classes only match themselves.
+        assert!(!production.matches(&string, 1));
+
+        production.define_class(class, leaves());
+        assert!( production.matches(&string, 1));
+        assert!(!production.matches(&string, 3));
+        assert!(!production.matches(&parse_prod_string("L3 X L1").unwrap(), 1));
+
+        let head = |s: &str| {
+            let mut head = parser::parse_production_head(s).unwrap();
+            head.define_class(class, leaves());
+            head
+        };
+
+        assert!( head("* < X").overlaps(&head("A < X")));
+        assert!( head("{Leaf} < X").overlaps(&head("L1 < X")));
+        assert!(!head("{Leaf} < X").overlaps(&head("A < X")));
+        assert!( head("* < X").subsumes(&head("{Leaf} < X")));
+        assert!( head("{Leaf} < X").subsumes(&head("L2 < X")));
+        assert!(!head("{Leaf} < X").subsumes(&head("* < X")));
+        assert!(!head("L2 < X").subsumes(&head("{Leaf} < X")));
+    }
+
+    #[test]
+    fn display_production() {
+        let production: Production = "A B < X > C -> F F".parse().unwrap();
+        assert_eq!(production.to_string(), "A B < X > C -> F F");
+
+        let production: Production = "X -> 0.5 F".parse().unwrap();
+        assert_eq!(production.to_string(), "X -> 0.5 F");
+
+        let production: Production = "X ->".parse().unwrap();
+        assert_eq!(production.to_string(), "X -> ");
+
+        let production: Production = "{Leaf} * < X > * -> F".parse().unwrap();
+        assert_eq!(production.to_string(), "{Leaf} * < X > * -> F");
+
+        let production: Production = "A < X ->(-1) 0.5 F".parse().unwrap();
+        assert_eq!(production.to_string(), "A < X ->(-1) 0.5 F");
+        assert_eq!(production.to_string().parse::<Production>().unwrap().priority(), -1);
+    }
+
+    #[test]
+    fn choosing_body_indices() {
+        let mut production: Production = "X -> 0.5 F".parse().unwrap();
+        assert_eq!(production.choose_body_index().unwrap(), 0);
+
+        production.add_body(parser::parse_production_body("0.5 G").unwrap());
+        for _ in 0..10 {
+            assert!(production.choose_body_index().unwrap() < 2);
+        }
+
+        production.add_body(parser::parse_production_body("0.5 H").unwrap());
+        assert!(production.choose_body_index().is_err());
+    }
+}
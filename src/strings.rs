@@ -24,6 +24,7 @@
 //! ```
 //!
 
+use std::collections::HashMap;
 use std::fmt::{Display, Formatter, Write};
 use std::iter::Cloned;
 use std::ops::Index;
@@ -32,6 +33,10 @@ use std::str::FromStr;
 
 use crate::parser::parse_prod_string;
 use crate::prelude::*;
+use crate::strings::tree::BranchTree;
+
+pub mod statistics;
+pub mod tree;
 
 /// Represents strings in our L-system. Strings
 /// are made up of a list of [`Symbol`] objects.
@@ -94,6 +99,95 @@ impl ProductionString {
     pub fn iter(&self) -> Iter<'_, Symbol> {
         self.symbols.iter()
     }
+
+    /// Returns the number of times each symbol occurs in the string. This is
+    /// also known as the string's *Parikh vector*.
+    ///
+    /// ```
+    /// use rusty_systems::prelude::*;
+    /// let string: ProductionString = "F F X F".parse().unwrap();
+    /// let counts = string.symbol_counts();
+    /// assert_eq!(counts[&"F".parse().unwrap()], 3);
+    /// ```
+    pub fn symbol_counts(&self) -> HashMap<Symbol, usize> {
+        let mut counts = HashMap::new();
+        for symbol in &self.symbols {
+            *counts.entry(*symbol).or_default() += 1;
+        }
+
+        counts
+    }
+
+    /// Returns the number of times the given symbol occurs in the string.
+    pub fn count(&self, symbol: Symbol) -> usize {
+        self.symbols.iter().filter(|s| **s == symbol).count()
+    }
+
+    /// Returns the number of branches in the string, being the number of
+    /// times the `push` symbol (such as `[`) occurs.
+    #[inline]
+    pub fn branch_count(&self, push: Symbol) -> usize {
+        self.count(push)
+    }
+
+    /// Returns the deepest level of branch nesting in the string, where branches start with
+    /// `push` and end with `pop`. A string with no branches has a depth of zero.
+    ///
+    /// Unmatched `pop` symbols are ignored.
+    ///
+    /// ```
+    /// use rusty_systems::prelude::*;
+    /// let string: ProductionString = "F [ F [ F ] ] [ F ]".parse().unwrap();
+    /// let push = "[".parse().unwrap();
+    /// let pop = "]".parse().unwrap();
+    /// assert_eq!(string.max_branch_depth(push, pop), 2);
+    /// ```
+    pub fn max_branch_depth(&self, push: Symbol, pop: Symbol) -> usize {
+        let mut depth = 0_usize;
+        let mut max = 0_usize;
+
+        for symbol in &self.symbols {
+            if *symbol == push {
+                depth += 1;
+                max = max.max(depth);
+            } else if *symbol == pop {
+                depth = depth.saturating_sub(1);
+            }
+        }
+
+        max
+    }
+
+    /// Removes every occurrence of the `cut` symbol, along with the remainder of the branch
+    /// it occurs in. Branches start with `push` and end with `pop`. The `pop` symbol closing the branch
+    /// is kept, so that balanced strings remain balanced. A `cut` that is not inside any branch removes
+    /// the remainder of the string.
+    ///
+    /// ```
+    /// use rusty_systems::prelude::*;
+    /// let string: ProductionString = "A [ B % C [ D ] E ] F % G".parse().unwrap();
+    /// let string = string.apply_cut("%".parse().unwrap(), "[".parse().unwrap(), "]".parse().unwrap());
+    /// assert_eq!(string.to_string(), "A [ B ] F");
+    /// ```
+    ///
+    /// See [`System::with_cut_symbol`].
+    pub fn apply_cut(self, cut: Symbol, push: Symbol, pop: Symbol) -> ProductionString {
+        if !self.symbols.contains(&cut) {
+            return self;
+        }
+
+        ProductionString::from(cut_items(self.symbols, Cutter::new(cut, push, pop), |symbol| *symbol))
+    }
+
+    /// Returns the branching structure of this string, where branches start with `push`
+    /// and end with `pop`. See [`BranchTree`].
+    ///
+    /// This returns an [`ErrorKind::Branching`](crate::error::ErrorKind::Branching) error if the
+    /// branches are not balanced.
+    #[inline]
+    pub fn branch_tree(&self, push: Symbol, pop: Symbol) -> crate::Result<BranchTree> {
+        BranchTree::build(self, push, pop)
+    }
 }
 
 impl Default for ProductionString {
@@ -110,6 +204,15 @@ impl From<Vec<Symbol>> for ProductionString {
     }
 }
 
+/// Create a [`ProductionString`] from a collection of [`Symbol`] objects.
+impl FromIterator<Symbol> for ProductionString {
+    fn from_iter<T: IntoIterator<Item=Symbol>>(iter: T) -> Self {
+        ProductionString {
+            symbols: iter.into_iter().collect()
+        }
+    }
+}
+
 impl Index<usize> for ProductionString {
     type Output = Symbol;
 
@@ -154,7 +257,7 @@ impl Display for ProductionString {
             } else {
                 first = false;
             }
-            f.write_str(symbol.to_string().as_str())?;
+            write!(f, "{symbol}")?;
         }
         
         Ok(())
@@ -171,6 +274,47 @@ impl FromStr for ProductionString {
     }
 }
 
+/// Decides which symbols are removed by a cut symbol, one symbol at a time, following the rules
+/// of [`ProductionString::apply_cut`].
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Cutter {
+    cut: Symbol,
+    push: Symbol,
+    pop: Symbol,
+    /// While cutting, the depth of branching below the cut.
+    cutting: Option<usize>
+}
+
+impl Cutter {
+    pub(crate) fn new(cut: Symbol, push: Symbol, pop: Symbol) -> Self {
+        Cutter { cut, push, pop, cutting: None }
+    }
+
+    /// Returns true iff the symbol, which follows those already given to the cutter, is kept.
+    pub(crate) fn keeps(&mut self, symbol: Symbol) -> bool {
+        match self.cutting {
+            None if symbol == self.cut => self.cutting = Some(0),
+            None => return true,
+            Some(depth) if symbol == self.push => self.cutting = Some(depth + 1),
+            Some(0) if symbol == self.pop => {
+                self.cutting = None;
+                return true;
+            }
+            Some(depth) if symbol == self.pop => self.cutting = Some(depth - 1),
+            Some(_) => {}
+        }
+
+        false
+    }
+}
+
+/// Removes the items that the cutter does not keep, where `symbol` gives the symbol of each item.
+pub(crate) fn cut_items<T, F>(items: Vec<T>, mut cutter: Cutter, symbol: F) -> Vec<T>
+    where F: Fn(&T) -> Symbol
+{
+    items.into_iter().filter(|item| cutter.keeps(symbol(item))).collect()
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -183,4 +327,40 @@ mod tests {
         assert!(empty.is_empty());
         assert_eq!(empty.len(), 0);
     }
+
+    #[test]
+    fn counting_symbols() {
+        let string: ProductionString = "F [ + F [ X ] ] - F [ X ]".parse().unwrap();
+        let push = Symbol::build("[").unwrap();
+        let pop = Symbol::build("]").unwrap();
+
+        assert_eq!(string.count(Symbol::build("F").unwrap()), 3);
+        assert_eq!(string.branch_count(push), 3);
+        assert_eq!(string.max_branch_depth(push, pop), 2);
+
+        let counts = string.symbol_counts();
+        assert_eq!(counts.len(), 6);
+        assert_eq!(counts.values().sum::<usize>(), string.len());
+
+        assert_eq!(ProductionString::empty().max_branch_depth(push, pop), 0);
+    }
+
+    #[test]
+    fn cutting_branches() {
+        let cut = Symbol::build("%").unwrap();
+        let push = Symbol::build("[").unwrap();
+        let pop = Symbol::build("]").unwrap();
+        let check = |input: &str, expected: &str| {
+            let string: ProductionString = input.parse().unwrap();
+            assert_eq!(string.apply_cut(cut, push, pop).to_string(), expected, "{input}");
+        };
+
+        check("A B C", "A B C");
+        check("A % B C", "A");
+        check("A [ B % C ] D", "A [ B ] D");
+        check("A [ B % C [ D [ E ] ] F ] G", "A [ B ] G");
+        check("A [ B [ C % D ] E % F ] G", "A [ B [ C ] E ] G");
+        check("A [ B [ % C ] D ] E [ % ] F", "A [ B [ ] D ] E [ ] F");
+        check("[ A % [ B ] ] [ C ] %", "[ A ] [ C ]");
+    }
 }
\ No newline at end of file
@@ -0,0 +1,204 @@
+//! Statistics about the symbols in [`ProductionString`] instances, generation by generation.
+//!
+//! When tuning productions it is useful to know how many of each symbol a string has,
+//! and how branched it is, at every step of its derivation. [`Statistics`] records this,
+//! and can be filled in while deriving using [`System::derive_with`]:
+//!
+//! ```
+//! use rusty_systems::prelude::*;
+//! use rusty_systems::strings::statistics::Statistics;
+//!
+//! let system = System::new();
+//! system.add_production("X -> Forward [ + X ] [ - X ]").unwrap();
+//!
+//! let mut statistics = Statistics::new("[".parse().unwrap(), "]".parse().unwrap());
+//! let axiom = "X".parse().unwrap();
+//! system.derive_with(axiom, RunSettings::for_max_iterations(3), |generation, string| {
+//!     statistics.record(generation, string);
+//! }).unwrap();
+//!
+//! let last = statistics.generations().last().unwrap();
+//! assert_eq!(last.generation, 3);
+//! assert_eq!(last.max_depth, 3);
+//!
+//! println!("{statistics}");
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+
+use crate::prelude::*;
+
+/// Statistics about a single generation of a derivation.
+#[derive(Debug, Clone)]
+pub struct GenerationStatistics {
+    /// The derivation step that produced the string. The axiom is generation zero.
+    pub generation: usize,
+    /// The length of the string.
+    pub length: usize,
+    /// The number of branches in the string. See [`ProductionString::branch_count`].
+    pub branches: usize,
+    /// The deepest nesting of branches. See [`ProductionString::max_branch_depth`].
+    pub max_depth: usize,
+    /// The number of times each symbol occurs. See [`ProductionString::symbol_counts`].
+    pub counts: HashMap<Symbol, usize>
+}
+
+impl GenerationStatistics {
+    /// Calculates the statistics for the given string, using `push` and `pop` as
+    /// the symbols that start and end branches.
+    pub fn build(generation: usize, string: &ProductionString, push: Symbol, pop: Symbol) -> Self {
+        GenerationStatistics {
+            generation,
+            length: string.len(),
+            branches: string.branch_count(push),
+            max_depth: string.max_branch_depth(push, pop),
+            counts: string.symbol_counts()
+        }
+    }
+
+    /// Returns the number of times the given symbol occurs in this generation.
+    pub fn count(&self, symbol: Symbol) -> usize {
+        self.counts.get(&symbol).copied().unwrap_or(0)
+    }
+}
+
+/// A table of [`GenerationStatistics`], one row per generation.
+///
+/// Displaying this prints an aligned table. See [`Statistics::to_csv`]
+/// for comma separated output.
+#[derive(Debug, Clone)]
+pub struct Statistics {
+    push: Symbol,
+    pop: Symbol,
+    generations: Vec<GenerationStatistics>
+}
+
+impl Statistics {
+    /// Creates an empty table, where branches are started by `push` and ended by `pop`.
+    pub fn new(push: Symbol, pop: Symbol) -> Self {
+        Statistics {
+            push,
+            pop,
+            generations: Vec::new()
+        }
+    }
+
+    /// Adds the statistics for the given string to the table.
+    pub fn record(&mut self, generation: usize, string: &ProductionString) {
+        self.generations.push(GenerationStatistics::build(generation, string, self.push, self.pop));
+    }
+
+    /// The recorded generations, in the order they were recorded.
+    pub fn generations(&self) -> &[GenerationStatistics] {
+        &self.generations
+    }
+
+    /// Every symbol that occurs in any of the recorded generations, sorted by name.
+    pub fn symbols(&self) -> Vec<Symbol> {
+        let symbols: HashSet<Symbol> = self.generations.iter()
+            .flat_map(|g| g.counts.keys().copied())
+            .collect();
+
+        let mut symbols: Vec<Symbol> = symbols.into_iter().collect();
+        symbols.sort_by_key(|s| s.to_string());
+        symbols
+    }
+
+    /// Returns the table as comma separated values, with a header row.
+    pub fn to_csv(&self) -> String {
+        let symbols = self.symbols();
+        let mut result = String::new();
+
+        let mut header = vec![String::from("generation"), String::from("length"),
+                              String::from("branches"), String::from("max_depth")];
+        header.extend(symbols.iter().map(|s| csv_escape(&s.to_string())));
+        result.push_str(&header.join(","));
+        result.push('\n');
+
+        for row in self.rows(&symbols) {
+            result.push_str(&row.join(","));
+            result.push('\n');
+        }
+
+        result
+    }
+
+    fn rows(&self, symbols: &[Symbol]) -> Vec<Vec<String>> {
+        self.generations.iter()
+            .map(|g| {
+                let mut row = vec![g.generation.to_string(), g.length.to_string(),
+                                   g.branches.to_string(), g.max_depth.to_string()];
+                row.extend(symbols.iter().map(|s| g.count(*s).to_string()));
+                row
+            })
+            .collect()
+    }
+}
+
+impl Display for Statistics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let symbols = self.symbols();
+
+        let mut header = vec![String::from("generation"), String::from("length"),
+                              String::from("branches"), String::from("max depth")];
+        header.extend(symbols.iter().map(|s| s.to_string()));
+
+        let rows = self.rows(&symbols);
+        let widths: Vec<usize> = header.iter()
+            .enumerate()
+            .map(|(i, h)| rows.iter().map(|r| r[i].len()).chain(std::iter::once(h.len())).max().unwrap_or(0))
+            .collect();
+
+        for row in std::iter::once(&header).chain(rows.iter()) {
+            let line: Vec<String> = row.iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{cell: >width$}"))
+                .collect();
+            writeln!(f, "{}", line.join("  "))?;
+        }
+
+        Ok(())
+    }
+}
+
+fn csv_escape(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        return format!("\"{}\"", value.replace('"', "\"\""));
+    }
+
+    value.to_string()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_prod_string;
+
+    #[test]
+    fn records_each_generation() {
+        let system = System::new();
+        system.add_production("X -> F [ X ] X").unwrap();
+
+        let mut statistics = Statistics::new(Symbol::build("[").unwrap(), Symbol::build("]").unwrap());
+        let axiom = parse_prod_string("X").unwrap();
+        let result = system.derive_with(axiom, RunSettings::for_max_iterations(2), |generation, string| {
+            statistics.record(generation, string);
+        }).unwrap();
+
+        assert_eq!(statistics.generations().len(), 3);
+
+        let last = statistics.generations().last().unwrap();
+        assert_eq!(last.length, result.len());
+        assert_eq!(last.branches, 3);
+        assert_eq!(last.max_depth, 2);
+        assert_eq!(last.count(Symbol::build("X").unwrap()), 4);
+
+        let csv = statistics.to_csv();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next().unwrap(), "generation,length,branches,max_depth,F,X,[,]");
+        assert_eq!(lines.next().unwrap(), "0,1,0,0,0,1,0,0");
+        assert_eq!(lines.next().unwrap(), "1,5,1,1,1,2,1,1");
+    }
+}
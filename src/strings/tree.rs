@@ -0,0 +1,274 @@
+//! A tree view of the branches in a bracketed [`ProductionString`].
+//!
+//! Strings such as `F [ + F ] [ - F X ] F` describe a branching structure using *push* and
+//! *pop* symbols (here `[` and `]`). A [`BranchTree`] makes that structure explicit: each
+//! [`BranchNode`] holds the symbols of one branch, and has the branches that start within it as its
+//! children.
+//!
+//! ```
+//! use rusty_systems::prelude::*;
+//! use rusty_systems::strings::tree::BranchTree;
+//!
+//! let string: ProductionString = "F [ + F ] [ - F X ] F".parse().unwrap();
+//! let tree = BranchTree::build(&string, "[".parse().unwrap(), "]".parse().unwrap()).unwrap();
+//!
+//! let root = tree.root();
+//! assert_eq!(root.symbols().len(), 2);     // The two F symbols outside the branches
+//! assert_eq!(root.children().count(), 2);
+//!
+//! // Trees can be turned back into the string they were built from.
+//! assert_eq!(tree.to_production_string(), string);
+//! ```
+//!
+//! Building a tree fails with an [`ErrorKind::Branching`](crate::error::ErrorKind::Branching) error
+//! if the string's branches are not balanced. The error's [`Error::index`] gives the position
+//! of the offending symbol.
+
+use std::ops::Range;
+
+use crate::prelude::*;
+use crate::Result;
+
+/// A single branch of a [`BranchTree`].
+#[derive(Debug, Clone)]
+pub struct BranchNode {
+    parent: Option<usize>,
+    depth: usize,
+    span: Range<usize>,
+    symbols: Vec<Symbol>,
+    /// Pairs of the offset into `symbols` that a child branch starts before, and the child's id.
+    children: Vec<(usize, usize)>
+}
+
+impl BranchNode {
+    /// The id of the branch that this branch grows from. The root has no parent.
+    #[inline]
+    pub fn parent(&self) -> Option<usize> {
+        self.parent
+    }
+
+    /// How deeply nested this branch is. The root has a depth of zero.
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// The range of indices in the original string that this branch covers, including
+    /// its push and pop symbols.
+    #[inline]
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// The symbols in this branch, not including those in its sub-branches.
+    #[inline]
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    /// The ids of the branches that grow from this branch, in order.
+    pub fn children(&self) -> impl Iterator<Item=usize> + '_ {
+        self.children.iter().map(|(_, child)| *child)
+    }
+
+    /// Returns true iff no branches grow from this one.
+    #[inline]
+    pub fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+}
+
+/// The branching structure of a [`ProductionString`]. See the [module documentation](crate::strings::tree).
+///
+/// Branches are identified by ids, which index into [`BranchTree::nodes`]. The root,
+/// which represents the whole string, always has the id `0`. Nodes are ordered by where they
+/// start in the string.
+#[derive(Debug, Clone)]
+pub struct BranchTree {
+    push: Symbol,
+    pop: Symbol,
+    nodes: Vec<BranchNode>
+}
+
+impl BranchTree {
+    /// Builds the tree for `string`, where branches are started by `push` and ended by `pop`.
+    pub fn build(string: &ProductionString, push: Symbol, pop: Symbol) -> Result<Self> {
+        let mut nodes = vec![BranchNode {
+            parent: None,
+            depth: 0,
+            span: 0..string.len(),
+            symbols: Vec::new(),
+            children: Vec::new()
+        }];
+        let mut stack = vec![0_usize];
+
+        for (index, symbol) in string.iter().enumerate() {
+            let current = *stack.last().unwrap();
+
+            if *symbol == push {
+                let id = nodes.len();
+                let offset = nodes[current].symbols.len();
+                nodes[current].children.push((offset, id));
+                nodes.push(BranchNode {
+                    parent: Some(current),
+                    depth: stack.len(),
+                    span: index..index,
+                    symbols: Vec::new(),
+                    children: Vec::new()
+                });
+                stack.push(id);
+            } else if *symbol == pop {
+                if stack.len() == 1 {
+                    return Err(Error::branching(index,
+                        format!("symbol {index} closes a branch that was never opened")));
+                }
+
+                nodes[current].span.end = index + 1;
+                stack.pop();
+            } else {
+                nodes[current].symbols.push(*symbol);
+            }
+        }
+
+        if stack.len() > 1 {
+            let unclosed = nodes[*stack.last().unwrap()].span.start;
+            return Err(Error::branching(unclosed,
+                format!("the branch opened at symbol {unclosed} is never closed")));
+        }
+
+        Ok(BranchTree { push, pop, nodes })
+    }
+
+    /// The branch representing the whole string.
+    #[inline]
+    pub fn root(&self) -> &BranchNode {
+        &self.nodes[0]
+    }
+
+    /// Returns the branch with the given id.
+    #[inline]
+    pub fn node(&self, id: usize) -> Option<&BranchNode> {
+        self.nodes.get(id)
+    }
+
+    /// All of the branches, including the root, ordered by where they start in the string.
+    #[inline]
+    pub fn nodes(&self) -> &[BranchNode] {
+        &self.nodes
+    }
+
+    /// The number of branches, including the root.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// A tree always has a root, and so is never empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// The ids of all the branches that have no sub-branches.
+    pub fn leaves(&self) -> impl Iterator<Item=usize> + '_ {
+        self.nodes.iter()
+            .enumerate()
+            .filter(|(_, node)| node.is_leaf())
+            .map(|(id, _)| id)
+    }
+
+    /// The ids of the branches from the given branch up to the root, starting with
+    /// the given branch itself.
+    pub fn ancestors(&self, id: usize) -> impl Iterator<Item=usize> + '_ {
+        std::iter::successors(Some(id).filter(|id| *id < self.nodes.len()),
+                              move |id| self.nodes[*id].parent)
+    }
+
+    /// Flattens the tree back into a string, with branches enclosed by the push and
+    /// pop symbols the tree was built with.
+    pub fn to_production_string(&self) -> ProductionString {
+        let mut result = ProductionString::empty();
+        self.flatten(0, &mut result);
+        result
+    }
+
+    fn flatten(&self, id: usize, result: &mut ProductionString) {
+        let node = &self.nodes[id];
+        let mut children = node.children.iter().peekable();
+
+        for offset in 0..=node.symbols.len() {
+            while let Some((_, child)) = children.next_if(|(start, _)| *start == offset) {
+                result.push_symbol(self.push);
+                self.flatten(*child, result);
+                result.push_symbol(self.pop);
+            }
+
+            if let Some(symbol) = node.symbols.get(offset) {
+                result.push_symbol(*symbol);
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::parser::parse_prod_string;
+
+    fn brackets() -> (Symbol, Symbol) {
+        (Symbol::build("[").unwrap(), Symbol::build("]").unwrap())
+    }
+
+    #[test]
+    fn builds_nested_branches() {
+        let (push, pop) = brackets();
+        let string = parse_prod_string("F [ A [ B ] C ] G [ D ] H").unwrap();
+        let tree = string.branch_tree(push, pop).unwrap();
+
+        assert_eq!(tree.len(), 4);
+        assert_eq!(tree.root().symbols().len(), 3);
+        assert_eq!(tree.root().children().collect::<Vec<_>>(), [1, 3]);
+
+        let first = tree.node(1).unwrap();
+        assert_eq!(first.parent(), Some(0));
+        assert_eq!(first.depth(), 1);
+        assert_eq!(first.span(), 1..8);
+        assert_eq!(first.symbols().len(), 2);
+
+        let nested = tree.node(2).unwrap();
+        assert_eq!(nested.depth(), 2);
+        assert_eq!(nested.span(), 3..6);
+        assert!(nested.is_leaf());
+
+        assert_eq!(tree.leaves().collect::<Vec<_>>(), [2, 3]);
+        assert_eq!(tree.ancestors(2).collect::<Vec<_>>(), [2, 1, 0]);
+    }
+
+    #[test]
+    fn round_trips() {
+        let (push, pop) = brackets();
+        for text in ["", "F", "[ ]", "[ [ ] ] F", "F [ A [ B ] C ] G [ D ] [ E ] H", "[ A ] [ B ]"] {
+            let string = parse_prod_string(text).unwrap();
+            let tree = string.branch_tree(push, pop).unwrap();
+            assert_eq!(tree.to_production_string(), string, "{text}");
+        }
+    }
+
+    #[test]
+    fn reports_unbalanced_positions() {
+        let (push, pop) = brackets();
+
+        let error = parse_prod_string("F [ F ] ] F").unwrap().branch_tree(push, pop).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::Branching));
+        assert_eq!(error.index(), Some(4));
+
+        let error = parse_prod_string("F [ F [ F ] F").unwrap().branch_tree(push, pop).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::Branching));
+        assert_eq!(error.index(), Some(1));
+
+        let error = parse_prod_string("[ F [ F").unwrap().branch_tree(push, pop).unwrap_err();
+        assert_eq!(error.index(), Some(2));
+    }
+}
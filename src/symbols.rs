@@ -6,7 +6,10 @@
 //!   production rules.
 //! * Variable / Production symbols, are those that can be rewritten by production rules.
 //!
-//! Rusty-Systems does not keep track of, or enforce, these kinds.
+//! Rusty-Systems does not keep track of these kinds for individual symbols. A
+//! [`SystemFamily`](crate::prelude::SystemFamily) declares them, and a
+//! [`System`](crate::prelude::System) can enforce them using
+//! [`System::with_strict_family`](crate::prelude::System::with_strict_family).
 //!
 //! # Creating a symbol
 //!
@@ -30,12 +33,26 @@ use crate::error::Error;
 
 pub mod iterator;
 
-type CodeStoreType = RwLock<HashMap<String, u32>>;
-type NameStoreType = RwLock<HashMap<u32, String>>;
+type CodeStoreType = RwLock<HashMap<&'static str, u32>>;
+type NameSegment = Box<[OnceLock<&'static str>]>;
 
 static CODE_REGISTER: OnceLock<CodeStoreType> = OnceLock::new();
-static NAME_REGISTER: OnceLock<NameStoreType> = OnceLock::new();
-static SYMBOL_ID: AtomicU32 = AtomicU32::new(100);
+static SYMBOL_ID: AtomicU32 = AtomicU32::new(FIRST_CODE);
+
+/// The code given to the first symbol.
+const FIRST_CODE: u32 = 100;
+/// The number of names held by the first segment of the name register. Each segment holds
+/// twice as many as the one before, so that the segments can hold a name for every code.
+const SEGMENT_SIZE: usize = 64;
+
+/// The names of each code, in order of code. This is only ever appended to, and so names can be
+/// read without taking a lock.
+static NAME_REGISTER: [OnceLock<NameSegment>; 32] = [EMPTY_SEGMENT; 32];
+
+/// The initial value of each segment of the name register. Arrays can only repeat a value that
+/// is not `Copy` if it is a constant, and this is only ever copied into [`NAME_REGISTER`].
+#[allow(clippy::declare_interior_mutable_const)]
+const EMPTY_SEGMENT: OnceLock<NameSegment> = OnceLock::new();
 
 /// Attempts to return a symbol code for a string.
 /// 
@@ -50,7 +67,8 @@ static SYMBOL_ID: AtomicU32 = AtomicU32::new(100);
 /// * The `name` is empty, or only white space.
 /// * The locks this function uses are poisoned.
 ///
-/// This is a thread safe call.
+/// This is a thread safe call. Names that have been seen before only need a read lock,
+/// and so can be looked up by many threads at once.
 /// 
 /// <div class="warning">
 /// 
@@ -59,24 +77,34 @@ static SYMBOL_ID: AtomicU32 = AtomicU32::new(100);
 /// 
 /// </div>
 pub fn get_code(name: &str) -> Result<u32, Error> {
-    let mut register = get_code_register().write()?;
-    let name = name.trim().to_string();
+    let name = name.trim();
 
     if name.is_empty() {
         return Err(Error::general("name should not be an empty string"))
     }
 
-    if let Some(code) = register.get(&name) {
+    if let Some(code) = get_code_register().read()?.get(name) {
+        return Ok(*code);
+    }
+
+    let mut register = get_code_register().write()?;
+
+    // Another thread may have registered the name since the read lock was released.
+    if let Some(code) = register.get(name) {
         return Ok(*code);
     }
 
+    // Names are never removed, and so live for the rest of the program.
+    let name: &'static str = Box::leak(name.to_string().into_boxed_str());
     let code = SYMBOL_ID.fetch_add(1, Ordering::SeqCst);
-    register.insert(name.clone(), code);
-    drop(register);
 
-    let mut register = get_name_register().write()?;
-    register.insert(code, name);
-    drop(register);
+    // The name is recorded before the code is, so that any thread that can see
+    // the code can also see its name.
+    let (segment, offset) = name_slot(code);
+    let _ = NAME_REGISTER[segment]
+        .get_or_init(|| (0..SEGMENT_SIZE << segment).map(|_| OnceLock::new()).collect())[offset]
+        .set(name);
+    register.insert(name, code);
 
     Ok(code)
 }
@@ -84,8 +112,17 @@ pub fn get_code(name: &str) -> Result<u32, Error> {
 /// If a code was previously returned for a symbol name, this returns
 /// that name.
 pub fn get_name(code: u32) -> Option<String> {
-    let register = get_name_register().read().ok()?;
-    register.get(&code).cloned()
+    get_name_ref(code).map(String::from)
+}
+
+/// As [`get_name`], but without copying the name. This does not take any locks.
+pub fn get_name_ref(code: u32) -> Option<&'static str> {
+    if code < FIRST_CODE {
+        return None;
+    }
+
+    let (segment, offset) = name_slot(code);
+    NAME_REGISTER.get(segment)?.get()?[offset].get().copied()
 }
 
 fn get_code_register() -> &'static CodeStoreType {
@@ -94,10 +131,12 @@ fn get_code_register() -> &'static CodeStoreType {
     })
 }
 
-fn get_name_register() -> &'static NameStoreType {
-    NAME_REGISTER.get_or_init(|| {
-        RwLock::new(HashMap::new())
-    })
+/// The segment of the name register that holds the name of the code, and its offset in the segment.
+/// The code must be at least [`FIRST_CODE`].
+fn name_slot(code: u32) -> (usize, usize) {
+    let index = (code - FIRST_CODE) as usize;
+    let segment = (index / SEGMENT_SIZE + 1).ilog2() as usize;
+    (segment, index - SEGMENT_SIZE * ((1 << segment) - 1))
 }
 
 
@@ -151,12 +190,17 @@ impl Symbol {
     pub fn name(&self) -> Option<String> {
         get_name(self.code)
     }
+
+    /// Returns the name associated with the symbol, without copying it. See [`get_name_ref`].
+    pub fn name_ref(&self) -> Option<&'static str> {
+        get_name_ref(self.code)
+    }
 }
 
 impl Display for Symbol {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        if let Some(name) = get_name(self.code) {
-            return f.write_str(name.as_str());
+        if let Some(name) = get_name_ref(self.code) {
+            return f.write_str(name);
         }
 
         write!(f, "code:{}", self.code)
@@ -167,7 +211,7 @@ impl TryFrom<u32> for Symbol {
     type Error = Error;
 
     fn try_from(value: u32) -> Result<Self, Self::Error> {
-        if get_name(value).is_none() {
+        if get_name_ref(value).is_none() {
             return Err(Error::general(format!("Unable to find symbol for code {value}")));
         }
 
@@ -279,4 +323,35 @@ mod tests {
         assert_eq!(get_name(code).unwrap(), "d");
         assert_eq!(get_name(code2).unwrap(), "d");
     }
+
+    #[test]
+    fn name_slots_cover_every_code() {
+        assert_eq!(name_slot(FIRST_CODE), (0, 0));
+        assert_eq!(name_slot(FIRST_CODE + 63), (0, 63));
+        assert_eq!(name_slot(FIRST_CODE + 64), (1, 0));
+        assert_eq!(name_slot(FIRST_CODE + 191), (1, 127));
+        assert_eq!(name_slot(FIRST_CODE + 192), (2, 0));
+
+        let (segment, offset) = name_slot(u32::MAX);
+        assert!(segment < NAME_REGISTER.len());
+        assert!(offset < SEGMENT_SIZE << segment);
+        assert!(get_name_ref(u32::MAX).is_none());
+    }
+
+    #[test]
+    fn threads_share_codes() {
+        let names: Vec<String> = (0..500).map(|i| format!("Threaded{i}")).collect();
+
+        let codes: Vec<Vec<u32>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..4)
+                .map(|_| scope.spawn(|| names.iter().map(|n| get_code(n).unwrap()).collect::<Vec<_>>()))
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        assert!(codes.iter().all(|c| *c == codes[0]));
+        for (name, code) in names.iter().zip(&codes[0]) {
+            assert_eq!(get_name_ref(*code), Some(name.as_str()));
+        }
+    }
 }
\ No newline at end of file
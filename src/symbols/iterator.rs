@@ -32,7 +32,7 @@ impl SymbolIterable for ProductionHead {
             Some(val) => Box::new(val.all_symbols_iter())
         };
 
-        let target = std::iter::once(self.target()).cloned();
+        let target = self.predecessor().into_iter();
 
         let post : Box<dyn Iterator<Item=Symbol>> = match self.post_context() {
             None => Box::new(std::iter::empty()),
@@ -32,6 +32,26 @@
 //! assert!( production.matches(&string, 2));       // Matches the last S
 //! ``` 
 //! 
+//! When several productions match the same symbol, the most specific one is used:
+//! productions with a higher [`Production::priority`] win, and after that those with the longest
+//! context. This means that context-sensitive productions take precedence over context-free
+//! ones, regardless of the order in which they were added.
+//!
+//! ```
+//! # use rusty_systems::prelude::*;
+//! # use rusty_systems::parser;
+//! let system = System::default();
+//! system.add_production("S -> S S").unwrap();
+//! system.add_production("G < S -> G").unwrap();
+//!
+//! let string = parser::parse_prod_string("G S").unwrap();
+//! let result = system.derive_once(string).unwrap();
+//! assert_eq!(result.to_string(), "G G");
+//! ```
+//!
+//! Productions that can match the same symbol with the same precedence are
+//! reported by [`System::ambiguities`].
+//!
 //! * todo discuss stochastic rules
 //!
 //! ## Collections of [`Symbol`] and [`Production`]
@@ -54,17 +74,35 @@
 
 use std::collections::HashSet;
 use std::ops::Deref;
-use std::sync::RwLock;
+use std::sync::{Arc, RwLock};
 
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{thread_rng, RngCore, SeedableRng};
+
+use crate::analysis;
+use crate::analysis::Report;
 use crate::error::{Error, ErrorKind};
 use crate::prelude::*;
+use crate::productions;
 use crate::productions::{Production, ProductionStore};
+use crate::system::environment::{respond_to_queries, Environment, Query, QueryLocator};
 use crate::system::family::TryIntoFamily;
+use crate::system::growth::GrowthMatrix;
+use crate::system::streaming::StreamingDerivation;
+use crate::system::table::ProductionTable;
+use crate::system::timed::TimedDerivation;
+use crate::strings::Cutter;
 use crate::symbols::{get_code, SymbolStore};
 use crate::symbols::iterator::SymbolIterable;
 use super::{Result};
 
+pub mod environment;
 pub mod family;
+pub mod growth;
+pub mod streaming;
+pub mod table;
+pub mod timed;
 
 /// Represents an L-system. This is the base for running the
 /// production rules.
@@ -81,14 +119,37 @@ pub mod family;
 #[derive(Debug)]
 pub struct System {
     symbols: RwLock<HashSet<u32>>,
-    productions: RwLock<Vec<Production>>
+    productions: RwLock<Vec<Production>>,
+    family: Option<Arc<SystemFamily>>,
+    cut: Option<Cut>,
+    sequence_heads: bool,
+    strict: bool
+}
+
+/// The symbols needed to prune branches. See [`System::with_cut_symbol`].
+#[derive(Debug, Copy, Clone)]
+struct Cut {
+    cut: Symbol,
+    push: Symbol,
+    pop: Symbol
+}
+
+impl Cut {
+    /// Decides which symbols of a string the cut removes.
+    fn cutter(&self) -> Cutter {
+        Cutter::new(self.cut, self.push, self.pop)
+    }
 }
 
 impl System {
     pub fn new() -> Self {
         System {
             symbols: RwLock::new(HashSet::new()),
-            productions: RwLock::new(Vec::new())
+            productions: RwLock::new(Vec::new()),
+            family: None,
+            cut: None,
+            sequence_heads: false,
+            strict: false
         }
     }
 
@@ -104,15 +165,110 @@ impl System {
     /// ```
     pub fn of_family<F: TryIntoFamily>(family: F) -> Result<Self> {
         let family = family.into_family()?;
-        let system = System::default();
+        let mut system = System::default();
 
         for symbol in family.symbols() {
             system.add_symbol(symbol.name.as_str())?;
         }
 
+        system.family = Some(family);
         Ok(system)
     }
 
+    /// Returns the family this system was created from, if it was created
+    /// using [`System::of_family`].
+    pub fn family(&self) -> Option<Arc<SystemFamily>> {
+        self.family.clone()
+    }
+
+    /// Returns this system, set to prune branches using the `cut` symbol.
+    ///
+    /// After every derivation step, each occurrence of `cut` is removed along with the
+    /// remainder of the branch it occurs in, up to (but not including) the `pop` symbol
+    /// that closes the branch. Branches are opened with `push`. A `cut` outside of any branch
+    /// removes the remainder of the string. This is the cut symbol, `%`, of section 1.10 of
+    /// [the Algorithmic Beauty of Plants][abop].
+    ///
+    /// ```
+    /// use rusty_systems::prelude::*;
+    ///
+    /// let system = System::new().with_cut_symbol(
+    ///     "%".parse().unwrap(), "[".parse().unwrap(), "]".parse().unwrap());
+    /// system.add_production("A -> %").unwrap();
+    ///
+    /// let string = "F [ F A F [ F ] F ] F".parse().unwrap();
+    /// let result = system.derive_once(string).unwrap();
+    /// assert_eq!(result.to_string(), "F [ F ] F");
+    /// ```
+    ///
+    /// See [`ProductionString::apply_cut`].
+    ///
+    /// [abop]: http://algorithmicbotany.org/papers/#abop
+    pub fn with_cut_symbol(mut self, cut: Symbol, push: Symbol, pop: Symbol) -> Self {
+        self.cut = Some(Cut { cut, push, pop });
+        self
+    }
+
+    /// The symbol this system uses to prune branches, if any. See [`System::with_cut_symbol`].
+    pub fn cut_symbol(&self) -> Option<Symbol> {
+        self.cut.map(|c| c.cut)
+    }
+
+    /// Returns this system allowing productions whose heads are a sequence of symbols, such
+    /// as `A B -> C`. By default, a head has a single symbol, as in classic L-Systems,
+    /// and adding a production with a sequence as its head is an error.
+    ///
+    /// Sequences are matched from left to right, and the symbols a sequence matches are not
+    /// matched again by other productions in the same derivation step.
+    ///
+    /// ```
+    /// use rusty_systems::prelude::*;
+    ///
+    /// let system = System::new().with_sequence_heads();
+    /// system.add_production("A B -> C").unwrap();
+    ///
+    /// let string = system.derive_once("A B B A B A".parse().unwrap()).unwrap();
+    /// assert_eq!(string.to_string(), "C B C A");
+    /// ```
+    ///
+    /// See [`ProductionHead::build_sequence`](crate::productions::ProductionHead::build_sequence).
+    pub fn with_sequence_heads(mut self) -> Self {
+        self.sequence_heads = true;
+        self
+    }
+
+    /// Whether productions may have a sequence of symbols as their head.
+    /// See [`System::with_sequence_heads`].
+    pub fn allows_sequence_heads(&self) -> bool {
+        self.sequence_heads
+    }
+
+    /// Returns this system enforcing the kinds of symbols declared by its family: adding a
+    /// production whose head is one of the family's terminals, or which uses a symbol that the
+    /// family does not declare, is an [`ErrorKind::Definitions`] error.
+    ///
+    /// This has no effect on systems without a family.
+    ///
+    /// ```
+    /// use rusty_systems::prelude::*;
+    /// use rusty_systems::interpretation::abop::abop_family;
+    ///
+    /// let system = System::of_family(abop_family()).unwrap().with_strict_family();
+    /// assert!(system.add_production("X -> Forward [ + X ]").is_ok());
+    /// assert!(system.add_production("+ -> -").is_err());          // + is a terminal
+    /// assert!(system.add_production("X -> Leaf").is_err());       // Leaf is not part of the family
+    /// ```
+    pub fn with_strict_family(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    /// Whether the kinds of symbols declared by the family are enforced.
+    /// See [`System::with_strict_family`].
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
     /// Run a single iteration of the productions on the given string.
     /// Returns [`None`] if an empty string is produced.
     pub fn derive_once(&self, string: ProductionString) -> Result<ProductionString> {
@@ -121,22 +277,129 @@ impl System {
         }
 
         if let Ok(productions) = self.productions.read() {
-            return derive_once(string, productions.deref());
+            let result = rewrite(string, &ProductionTable::new(productions.deref()), &mut thread_rng())?;
+            return Ok(apply_cut(result, self.cut.as_ref()));
         }
 
         Err(Error::general("Poisoned lock on production list"))
     }
 
     pub fn derive(&self, string: ProductionString, settings: RunSettings) -> Result<ProductionString> {
-        if string.is_empty() {
-            return Ok(ProductionString::empty())
-        }
+        self.derive_with(string, settings, |_, _| {})
+    }
 
-        if let Ok(productions) = self.productions.read() {
-            return derive(string, productions.deref(), settings);
+    /// Derives the string as [`System::derive`] does, but calls `observer` with
+    /// every generation of the derivation, starting with the original string
+    /// as generation zero.
+    ///
+    /// See [`statistics`](crate::strings::statistics) for an example.
+    pub fn derive_with<F>(&self, string: ProductionString, settings: RunSettings, observer: F) -> Result<ProductionString>
+        where F: FnMut(usize, &ProductionString)
+    {
+        let productions = self.productions.read()?;
+        derive_steps(string, productions.deref(), settings, self.cut.as_ref(), observer)
+    }
+
+    /// Derives an *open*, environmentally sensitive, L-System.
+    ///
+    /// After every derivation step, the `locator`, such as the
+    /// [ABOP turtle](crate::interpretation::abop::AbopTurtleInterpretation), locates each query
+    /// symbol in the string. If an
+    /// `environment` is given, it may then replace each query symbol with a new string, after which
+    /// the system's cut symbol, if any, is applied again.
+    ///
+    /// This returns the final string, as well as the location of each of its query symbols.
+    /// See the [environment module](crate::system::environment) for more information.
+    pub fn derive_open<L: QueryLocator + ?Sized>(&self,
+                                                 string: ProductionString,
+                                                 settings: RunSettings,
+                                                 locator: &L,
+                                                 environment: Option<&dyn Environment>) -> Result<(ProductionString, Vec<Query>)> {
+        let productions = self.productions.read()?;
+        let table = ProductionTable::new(productions.deref());
+        let mut rng = settings.rng();
+        let mut current = string;
+
+        for _ in 0..settings.max_iterations {
+            if current.is_empty() {
+                break;
+            }
+
+            current = apply_cut(rewrite_step(current, &table, settings.rewrite_mode, &mut rng)?, self.cut.as_ref());
+
+            if let Some(environment) = environment {
+                let queries = locator.locate_queries(self, &current)?;
+                current = apply_cut(respond_to_queries(current, &queries, environment), self.cut.as_ref());
+            }
+
+            check_length(&current, &settings)?;
         }
 
-        Err(Error::general("Poisoned lock on production list"))
+        let queries = locator.locate_queries(self, &current)?;
+        Ok((current, queries))
+    }
+
+    /// Derives the string as [`System::derive`] does, but produces the symbols of the final
+    /// generation one at a time, without holding any generation in memory. This only works
+    /// for context-free systems. See the [streaming module](crate::system::streaming).
+    pub fn derive_streaming(&self, axiom: ProductionString, settings: RunSettings) -> Result<StreamingDerivation> {
+        StreamingDerivation::new(self, axiom, settings)
+    }
+
+    /// Starts a derivation of `axiom` in which symbols are rewritten as they age, rather than
+    /// in discrete generations. See the [timed module](crate::system::timed).
+    pub fn timed(&self, axiom: ProductionString, settings: RunSettings) -> TimedDerivation<'_> {
+        TimedDerivation::new(self, axiom, settings)
+    }
+
+    /// Returns pairs of productions which can match the same symbols with
+    /// the same precedence. See [`find_ambiguities`].
+    ///
+    /// ```
+    /// use rusty_systems::prelude::*;
+    ///
+    /// let system = System::new();
+    /// system.add_production("A < X -> F").unwrap();
+    /// system.add_production("X > B -> G").unwrap();
+    ///
+    /// // Both productions match the X in "A X B".
+    /// assert_eq!(system.ambiguities().unwrap().len(), 1);
+    /// ```
+    pub fn ambiguities(&self) -> Result<Vec<(Production, Production)>> {
+        let productions = self.productions.read()?;
+        Ok(find_ambiguities(productions.deref())
+            .into_iter()
+            .map(|(first, second)| (first.clone(), second.clone()))
+            .collect())
+    }
+
+    /// Statically analyses the system's productions, starting from the given axiom.
+    ///
+    /// If the system was created with [`System::of_family`], symbols that are not
+    /// part of that family are also reported. See the [analysis module](crate::analysis)
+    /// for more information.
+    pub fn analyse<I: Interpretation>(&self, axiom: &ProductionString, interpretation: &I) -> Result<Report> {
+        let productions = self.productions.read()?;
+
+        // Cut symbols are handled by the system rather than the interpretation.
+        let mut handled = interpretation.interpreted_symbols();
+        handled.extend(self.cut_symbol().map(|s| s.to_string()));
+
+        Ok(analysis::analyse(productions.deref(),
+                             axiom,
+                             self.family.as_deref(),
+                             &handled))
+    }
+
+    /// Returns the growth matrix of this system, which can be used to predict the length of
+    /// derived strings without deriving them. See the [growth module](crate::system::growth).
+    ///
+    /// This fails if the system is not deterministic and context-free, or if its productions
+    /// produce its cut symbol (see [`System::with_cut_symbol`]).
+    pub fn growth_matrix(&self) -> Result<GrowthMatrix> {
+        let productions = self.productions.read()?;
+        check_no_cuts(productions.deref(), self.cut.as_ref())?;
+        GrowthMatrix::build(productions.deref())
     }
 
     /// Returns the number of production rules in the system.
@@ -183,19 +446,53 @@ impl ProductionStore for System {
     /// This is a thread safe operation. It registers the production and all of its
     /// symbols with the [`System`] instance.
     ///
-    /// The only error this returns is a poison error.
+    /// Productions whose head is a sequence of symbols are an [`ErrorKind::Definitions`] error,
+    /// unless the system allows them (see [`System::with_sequence_heads`]).
+    ///
+    /// Systems with a [strict family](System::with_strict_family) return [`ErrorKind::Definitions`]
+    /// errors for productions that rewrite terminals, or use symbols that the family does not declare.
+    ///
+    /// Symbol classes that the production's contexts refer to are defined using the classes
+    /// declared by the system's family. It is an [`ErrorKind::Definitions`] error to refer to a
+    /// class that the family does not declare.
     fn add_production<P>(&self, production: P) -> Result<Production>
     where
         P: TryInto<Production>,
         P::Error: Into<Error>
     {
-        let production = production.try_into().map_err(Into::into)?;
+        let mut production = production.try_into().map_err(Into::into)?;
+
+        if production.head().is_sequence() && !self.sequence_heads {
+            return Err(Error::definition(
+                format!("the head [{}] is a sequence of symbols, which this system does not allow. See System::with_sequence_heads", production.head())));
+        }
+
+        if let (true, Some(family)) = (self.strict, &self.family) {
+            check_kinds(&production, family)?;
+        }
+
+        //region Define the symbol classes the head refers to
+        for reference in production.head().class_references() {
+            if production.head().class_members(reference).is_some() {
+                continue;
+            }
+
+            let name = reference.to_string();
+            let name = productions::class_name(&name).unwrap_or(&name);
+            let members = self.family.as_ref()
+                .and_then(|family| family.class(name))
+                .ok_or_else(|| Error::definition(format!("the symbol class [{name}] has not been declared by the system's family")))?;
+            let members = members.iter().map(Symbol::build).collect::<Result<Vec<_>>>()?;
+            production.define_class(reference, members);
+        }
+        //endregion
+
         let lock = self.productions.write();
         if let Ok(mut productions) = lock {
             let head = production.head().clone();
 
             //region Register symbols with the system
-            for symbol in production.all_symbols_iter() {
+            for symbol in production.all_symbols_iter().filter(|s| !head.is_pattern(*s)) {
                 self.add_symbol(symbol)?;
             }
             //endregion 
@@ -225,12 +522,71 @@ impl Default for System {
 #[derive(Debug, Clone)]
 pub struct RunSettings {
     /// The maximum number of iterations allowed for a derivation.
-    pub max_iterations: usize
+    pub max_iterations: usize,
+    /// The maximum length of any string produced during a derivation. If [`None`],
+    /// strings may grow without limit.
+    ///
+    /// For deterministic, context-free systems rewriting in parallel, derivations that would exceed
+    /// this length are refused before any derivation occurs (see [`GrowthMatrix`]). Other systems
+    /// stop with an error as soon as a string exceeds the limit.
+    pub max_length: Option<usize>,
+    /// How the symbols of a string are rewritten in each iteration. See [`RewriteMode`].
+    pub rewrite_mode: RewriteMode,
+    /// Seeds the random choices that a derivation makes, so that it is repeatable. These are the
+    /// bodies chosen for stochastic productions, and the symbol rewritten when using
+    /// [`RewriteMode::RandomSequential`]. If [`None`], a new seed is taken from the system.
+    pub seed: Option<u64>
+}
+
+/// Determines which symbols are rewritten in each iteration of a derivation.
+///
+/// L-Systems rewrite every symbol at once, in parallel. Generative grammars, such as those
+/// of Chomsky, instead rewrite a single symbol at a time, which the sequential modes
+/// provide. In these modes, each iteration rewrites at most one symbol (or one sequence of symbols,
+/// see [`System::with_sequence_heads`]), chosen from those that some production matches.
+///
+/// ```
+/// use rusty_systems::prelude::*;
+/// use rusty_systems::system::RewriteMode;
+///
+/// let system = System::new();
+/// system.add_production("Name -> Syllable Syllable").unwrap();
+/// system.add_production("Syllable -> ka").unwrap();
+///
+/// let settings = RunSettings { rewrite_mode: RewriteMode::LeftmostSequential, ..RunSettings::for_max_iterations(2) };
+/// let result = system.derive("Name".parse().unwrap(), settings).unwrap();
+/// assert_eq!(result.to_string(), "ka Syllable");
+/// ```
+///
+/// [Timed derivations](crate::system::timed) always rewrite in parallel.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum RewriteMode {
+    /// Every symbol is rewritten at once, as in an L-System.
+    #[default]
+    Parallel,
+    /// Only the leftmost symbol that a production matches is rewritten.
+    LeftmostSequential,
+    /// A single symbol, chosen at random from those that a production matches, is rewritten.
+    RandomSequential
 }
 
 impl RunSettings {
     pub fn for_max_iterations(max_iterations: usize) -> Self {
-        RunSettings { max_iterations }
+        RunSettings { max_iterations, ..RunSettings::default() }
+    }
+
+    /// Returns these settings with the given [seed](RunSettings::seed).
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// The random number generator that a derivation using these settings should use.
+    fn rng(&self) -> StdRng {
+        match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy()
+        }
     }
 }
 
@@ -246,38 +602,97 @@ const DEFAULT_MAX_ITERATIONS : usize = 10;
 impl Default for RunSettings {
     fn default() -> Self {
         RunSettings {
-            max_iterations: DEFAULT_MAX_ITERATIONS
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+            max_length: None,
+            rewrite_mode: RewriteMode::default(),
+            seed: None
         }
     }
 }
 
 /// Given a vector of productions, this returns a reference to a
 /// production that matches the string at the given location.
+///
+/// If more than one production matches, the one that takes precedence
+/// (see [`Production::precedence_cmp`]) is returned: productions with a higher
+/// [`Production::priority`] are preferred, and then those with longer contexts.
+/// Any remaining ties are broken by returning the production that occurs first in `productions`.
+///
+/// This checks every production. When matching many positions of a string, a
+/// [`ProductionTable`] finds the same production more quickly.
 pub fn find_matching<'a>(productions: &'a [Production],
                      string: &ProductionString, index: usize) -> Option<&'a Production> {
+    let mut best: Option<&Production> = None;
+
     for production in productions {
-        if production.matches(string, index) {
-            return Some(production)
+        if !production.matches(string, index) {
+            continue;
+        }
+
+        match best {
+            Some(current) if !production.precedence_cmp(current).is_gt() => {}
+            _ => best = Some(production)
+        }
+    }
+
+    best
+}
+
+/// Returns all pairs of productions that are ambiguous with one another
+/// (see [`Production::is_ambiguous_with`]). For each pair, the production
+/// that [`find_matching`] will prefer is returned first.
+///
+/// Ambiguous productions are not an error, since [`find_matching`] is deterministic,
+/// but they are often a mistake in the grammar.
+pub fn find_ambiguities(productions: &[Production]) -> Vec<(&Production, &Production)> {
+    let mut result = Vec::new();
+
+    for (i, first) in productions.iter().enumerate() {
+        for second in &productions[i + 1..] {
+            if first.is_ambiguous_with(second) {
+                result.push((first, second));
+            }
         }
     }
 
-    None
+    result
 }
 
 /// Runs one step of an iteration, using the given production rules.
 ///
+/// Productions whose heads are a sequence of symbols (see [`System::with_sequence_heads`])
+/// replace the whole sequence, which is then skipped.
+///
 /// Most of the time you will want to make use of [`System::derive_once`]
 /// instead of trying to call this function directly.  
 pub fn derive_once(string: ProductionString, productions: &[Production]) -> Result<ProductionString> {
+    rewrite(string, &ProductionTable::new(productions), &mut thread_rng())
+}
+
+/// Runs one step of an iteration, rewriting the string as described by the [`RewriteMode`].
+///
+/// [`RewriteMode::Parallel`] is the same as [`derive_once`]. The random number generator
+/// chooses the bodies of stochastic productions, and the symbol rewritten when using
+/// [`RewriteMode::RandomSequential`].
+pub fn derive_step(string: ProductionString,
+                   productions: &[Production],
+                   mode: RewriteMode,
+                   rng: &mut dyn RngCore) -> Result<ProductionString> {
+    rewrite_step(string, &ProductionTable::new(productions), mode, rng)
+}
+
+/// Rewrites every symbol of the string, as [`derive_once`] does.
+fn rewrite(string: ProductionString, table: &ProductionTable, rng: &mut dyn RngCore) -> Result<ProductionString> {
     if string.is_empty() {
         return Ok(ProductionString::empty())
     }
 
     let mut result = ProductionString::default();
 
-    for (index, symbol) in string.symbols().iter().enumerate() {
-        if let Some(production) = find_matching(productions, &string, index) {
-            let body = production.body()?;
+    let mut index = 0;
+    while index < string.len() {
+        if let Some(production) = table.find(&string, index) {
+            let body = production.body_with(rng)?;
 
             // println!("body match: {index}: {:?}", body.string().iter().map(|t| t.code()).collect::<Vec<_>>());
 
@@ -286,12 +701,11 @@ pub fn derive_once(string: ProductionString, productions: &[Production]) -> Resu
                 .iter()
                 .cloned()
                 .for_each(|symbol| result.push_symbol(symbol));
-            continue;
+            index += production.head().predecessor_len();
         } else {
-            result.push_symbol(*symbol);
+            result.push_symbol(string[index]);
+            index += 1;
         }
-
-
     }
 
     match result.len() {
@@ -300,14 +714,140 @@ pub fn derive_once(string: ProductionString, productions: &[Production]) -> Resu
     }
 }
 
+/// Rewrites the string as described by the [`RewriteMode`], as [`derive_step`] does.
+fn rewrite_step(string: ProductionString,
+                table: &ProductionTable,
+                mode: RewriteMode,
+                rng: &mut dyn RngCore) -> Result<ProductionString> {
+    let found = match mode {
+        RewriteMode::Parallel => return rewrite(string, table, rng),
+        RewriteMode::LeftmostSequential => (0..string.len())
+            .find_map(|index| table.find(&string, index).map(|p| (index, p))),
+        RewriteMode::RandomSequential => {
+            let candidates: Vec<(usize, &Production)> = (0..string.len())
+                .filter_map(|index| table.find(&string, index).map(|p| (index, p)))
+                .collect();
+            candidates.choose(rng).copied()
+        }
+    };
+
+    let (index, production) = match found {
+        Some(found) => found,
+        None => return Ok(string)
+    };
+
+    let symbols = string.symbols();
+    let mut result = ProductionString::from(symbols[..index].to_vec());
+    production.body_with(rng)?.string().iter().for_each(|s| result.push_symbol(*s));
+    symbols[index + production.head().predecessor_len()..].iter().for_each(|s| result.push_symbol(*s));
+
+    Ok(result)
+}
+
+/// Ensures that a production only rewrites the family's productions, and only uses its symbols.
+fn check_kinds(production: &Production, family: &SystemFamily) -> Result<()> {
+    let head = production.head();
+
+    for symbol in head.predecessor() {
+        if family.is_terminal(symbol.to_string()) {
+            return Err(Error::definition(
+                format!("[{symbol}] is a terminal of the {} family, and cannot be rewritten", family.name())));
+        }
+    }
+
+    for symbol in production.all_symbols_iter() {
+        let name = symbol.to_string();
+        if symbol != productions::wildcard() && productions::class_name(&name).is_none() && !family.contains(&name) {
+            return Err(Error::definition(
+                format!("[{symbol}] is not a symbol of the {} family", family.name())));
+        }
+    }
+
+    Ok(())
+}
+
+fn check_length(string: &ProductionString, settings: &RunSettings) -> Result<()> {
+    if let Some(max_length) = settings.max_length {
+        if string.len() > max_length {
+            return Err(Error::execution(
+                format!("derivation produced a string longer than the limit of {max_length}")));
+        }
+    }
+
+    Ok(())
+}
+
+fn apply_cut(string: ProductionString, cut: Option<&Cut>) -> ProductionString {
+    match cut {
+        None => string,
+        Some(cut) => string.apply_cut(cut.cut, cut.push, cut.pop)
+    }
+}
+
+/// Cuts remove symbols, and so growth cannot be predicted for productions that produce them.
+fn check_no_cuts(productions: &[Production], cut: Option<&Cut>) -> Result<()> {
+    if let Some(cut) = cut {
+        let produces_cut = productions.iter()
+            .flat_map(|p| p.all_bodies())
+            .any(|body| body.string().iter().any(|s| *s == cut.cut));
+
+        if produces_cut {
+            return Err(Error::definition("growth cannot be predicted for productions that produce the cut symbol"));
+        }
+    }
+
+    Ok(())
+}
+
 pub fn derive(string: ProductionString, productions: &[Production], settings: RunSettings) -> Result<ProductionString> {
+    derive_with(string, productions, settings, |_, _| {})
+}
+
+/// Derives the string using the given productions, calling `observer` with every generation,
+/// starting with the given string as generation zero.
+///
+/// Most of the time you will want to make use of [`System::derive_with`] instead.
+pub fn derive_with<F>(string: ProductionString,
+                      productions: &[Production],
+                      settings: RunSettings,
+                      observer: F) -> Result<ProductionString>
+    where F: FnMut(usize, &ProductionString)
+{
+    derive_steps(string, productions, settings, None, observer)
+}
+
+fn derive_steps<F>(string: ProductionString,
+                   productions: &[Production],
+                   settings: RunSettings,
+                   cut: Option<&Cut>,
+                   mut observer: F) -> Result<ProductionString>
+    where F: FnMut(usize, &ProductionString)
+{
+    observer(0, &string);
+
     if string.is_empty() {
         return Ok(ProductionString::empty())
     }
 
+    if let (Some(max_length), RewriteMode::Parallel) = (settings.max_length, settings.rewrite_mode) {
+        let growth = check_no_cuts(productions, cut)
+            .and_then(|_| GrowthMatrix::build(productions));
+        if let Ok(growth) = growth {
+            let length = growth.length(&string, settings.max_iterations);
+            if length > max_length as u64 {
+                return Err(Error::execution(
+                    format!("derivation would produce a string of length {length}, which is more than the limit of {max_length}")));
+            }
+        }
+    }
+
+    let table = ProductionTable::new(productions);
+    let mut rng = settings.rng();
     let mut current = string;
-    for _ in 0..settings.max_iterations {
-        current = derive_once(current, productions)?;
+    for generation in 1..=settings.max_iterations {
+        current = apply_cut(rewrite_step(current, &table, settings.rewrite_mode, &mut rng)?, cut);
+        check_length(&current, &settings)?;
+        observer(generation, &current);
     }
 
     Ok(current)
@@ -320,7 +860,7 @@ pub fn derive(string: ProductionString, productions: &[Production], settings: Ru
 #[cfg(test)]
 mod tests {
     use std::thread;
-    use crate::parser::parse_prod_string;
+    use crate::parser::{parse_prod_string, parse_production};
     use super::*;
 
     #[test]
@@ -469,4 +1009,200 @@ mod tests {
         assert_eq!(string, parse_prod_string("S S S G X").unwrap());
     }
 
+    #[test]
+    fn context_sensitive_takes_precedence() {
+        let system = System::default();
+        system.add_production("S -> S S").unwrap();
+        system.add_production("G < S -> G").unwrap();
+        system.add_production("G < S > X -> X").unwrap();
+
+        let string = parse_prod_string("G S S G S X").unwrap();
+        let string = system.derive_once(string).unwrap();
+
+        assert_eq!(string, parse_prod_string("G G S S G X X").unwrap());
+    }
+
+    #[test]
+    fn priority_overrides_specificity() {
+        let productions = vec![
+            parse_production("G < S -> G").unwrap(),
+            parse_production("S -> S S").unwrap().with_priority(1),
+        ];
+
+        let string = parse_prod_string("G S").unwrap();
+        let found = find_matching(&productions, &string, 1).unwrap();
+        assert_eq!(found.head().specificity(), 0);
+    }
+
+    #[test]
+    fn ties_prefer_insertion_order() {
+        let productions = vec![
+            parse_production("A < X -> F").unwrap(),
+            parse_production("X > B -> G").unwrap(),
+        ];
+
+        let string = parse_prod_string("A X B").unwrap();
+        let found = find_matching(&productions, &string, 1).unwrap();
+        assert!(found.head().pre_context().is_some());
+
+        let ambiguities = find_ambiguities(&productions);
+        assert_eq!(ambiguities.len(), 1);
+    }
+
+    #[test]
+    fn cuts_after_each_step() {
+        let system = System::new().with_cut_symbol(
+            Symbol::build("%").unwrap(), Symbol::build("[").unwrap(), Symbol::build("]").unwrap());
+        system.add_production("A -> B").unwrap();
+        system.add_production("B -> %").unwrap();
+        system.add_production("X -> F [ A X ] [ X ]").unwrap();
+
+        let string = parse_prod_string("X").unwrap();
+        let string = system.derive_once(string).unwrap();
+        assert_eq!(string, parse_prod_string("F [ A X ] [ X ]").unwrap());
+
+        let string = system.derive_once(string).unwrap();
+        assert_eq!(string, parse_prod_string("F [ B F [ A X ] [ X ] ] [ F [ A X ] [ X ] ]").unwrap());
+
+        let string = system.derive_once(string).unwrap();
+        assert_eq!(string, parse_prod_string("F [ ] [ F [ B F [ A X ] [ X ] ] [ F [ A X ] [ X ] ] ]").unwrap());
+
+        let derived = system.derive(parse_prod_string("X").unwrap(), RunSettings::for_max_iterations(3)).unwrap();
+        assert_eq!(derived, string);
+        assert!(system.growth_matrix().is_err());
+    }
+
+    #[test]
+    fn sequence_heads() {
+        let system = System::new();
+        assert!(system.add_production("A B -> C").is_err());
+
+        let system = System::new().with_sequence_heads();
+        system.add_production("A B -> C").unwrap();
+        system.add_production("A -> D").unwrap();
+        system.add_production("B B -> E").unwrap();
+
+        // Matches do not overlap, and the longer head is preferred.
+        let string = parse_prod_string("A B B B A").unwrap();
+        assert_eq!(system.derive_once(string).unwrap(), parse_prod_string("C E D").unwrap());
+
+        system.add_production("X < B A > Y -> Z").unwrap();
+        let string = parse_prod_string("X B A Y B A Y").unwrap();
+        assert_eq!(system.derive_once(string).unwrap(), parse_prod_string("X Z Y B D Y").unwrap());
+    }
+
+    #[test]
+    fn sequential_rewriting() {
+        let system = System::new();
+        system.add_production("S -> a S b").unwrap();
+        system.add_production("T -> c").unwrap();
+
+        let axiom = parse_prod_string("S T S").unwrap();
+        let leftmost = RunSettings { rewrite_mode: RewriteMode::LeftmostSequential, ..RunSettings::for_max_iterations(2) };
+        let result = system.derive(axiom.clone(), leftmost).unwrap();
+        assert_eq!(result, parse_prod_string("a a S b b T S").unwrap());
+
+        // Each step rewrites a single S, adding an a and a b.
+        let axiom = parse_prod_string("S S").unwrap();
+        let random = RunSettings { rewrite_mode: RewriteMode::RandomSequential, ..RunSettings::for_max_iterations(3) };
+        let result = system.derive(axiom, random).unwrap();
+        assert_eq!(result.len(), 8);
+        assert_eq!(result.count(Symbol::build("S").unwrap()), 2);
+        assert_eq!(result.count(Symbol::build("a").unwrap()), 3);
+
+        // Seeded derivations choose the same symbols each time.
+        let system = System::new();
+        system.add_production("S -> S a S").unwrap();
+        let seeded = RunSettings { rewrite_mode: RewriteMode::RandomSequential, ..RunSettings::for_max_iterations(6) }
+            .with_seed(7);
+        let first = system.derive(parse_prod_string("S S").unwrap(), seeded.clone()).unwrap();
+        assert_eq!(system.derive(parse_prod_string("S S").unwrap(), seeded).unwrap(), first);
+
+        // Nothing left to rewrite.
+        let string = parse_prod_string("a b").unwrap();
+        assert_eq!(derive_step(string.clone(), &[], RewriteMode::RandomSequential, &mut thread_rng()).unwrap(), string);
+    }
+
+    #[test]
+    fn seeded_stochastic_derivations() {
+        let system = System::new();
+        system.add_production("Name -> 0.5 Syllable Name").unwrap();
+        system.add_production("Name -> 0.5 Syllable").unwrap();
+        system.add_production("Syllable -> ka").unwrap();
+        system.add_production("Syllable -> ri").unwrap();
+        system.add_production("Syllable -> to").unwrap();
+
+        let axiom = parse_prod_string("Name Name Name").unwrap();
+        let settings = RunSettings::for_max_iterations(8).with_seed(11);
+        let first = system.derive(axiom.clone(), settings.clone()).unwrap();
+        for _ in 0..5 {
+            assert_eq!(system.derive(axiom.clone(), settings.clone()).unwrap(), first);
+        }
+
+        let streamed: ProductionString = system.derive_streaming(axiom.clone(), settings.clone()).unwrap().collect();
+        assert_eq!(system.derive_streaming(axiom.clone(), settings.clone()).unwrap().collect::<ProductionString>(), streamed);
+
+        let productions = system.productions.read().unwrap();
+        let step = |seed| derive_step(axiom.clone(), &productions, RewriteMode::Parallel, &mut StdRng::seed_from_u64(seed)).unwrap();
+        assert_eq!(step(3), step(3));
+    }
+
+    #[test]
+    fn strict_families() {
+        let family = SystemFamily::define()
+            .with_production("Company", None)
+            .with_terminal("surname", None)
+            .build("StrictNames")
+            .unwrap();
+
+        let lenient = System::of_family(family.clone()).unwrap();
+        assert!(!lenient.is_strict());
+        assert!(lenient.add_production("surname -> Company").is_ok());
+
+        let strict = System::of_family(family).unwrap().with_strict_family();
+        assert!(strict.add_production("Company -> surname surname").is_ok());
+
+        let error = strict.add_production("surname -> Company").unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::Definitions));
+        assert!(error.to_string().contains("surname"));
+
+        let error = strict.add_production("Company -> surname hyphen surname").unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::Definitions));
+        assert!(error.to_string().contains("hyphen"));
+
+        // Without a family, there is nothing to enforce.
+        assert!(System::new().with_strict_family().add_production("a -> b").is_ok());
+    }
+
+    #[test]
+    fn classes_come_from_the_family() {
+        let family = SystemFamily::define()
+            .with_production("X", None)
+            .with_terminal("L1", None)
+            .with_class("Leaf", &["L1"])
+            .build("ClassFamily")
+            .unwrap();
+
+        let system = System::of_family(family).unwrap();
+        assert!(system.add_production("{Leaf} < X -> L1").is_ok());
+        assert!(system.get_symbol("{Leaf}").is_none());
+
+        let error = system.add_production("{Stem} < X -> L1").unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::Definitions));
+        assert!(System::new().add_production("{Leaf} < X -> L1").is_err());
+    }
+
+    #[test]
+    fn detects_ambiguities() {
+        let system = System::default();
+        system.add_production("X -> F").unwrap();
+        system.add_production("A < X -> F").unwrap();
+        system.add_production("B < X -> F").unwrap();
+        system.add_production("C B < X -> F").unwrap();
+        assert!(system.ambiguities().unwrap().is_empty());
+
+        system.add_production("X > A -> F").unwrap();
+        assert_eq!(system.ambiguities().unwrap().len(), 2);
+    }
+
 }
\ No newline at end of file
@@ -0,0 +1,213 @@
+//! Support for environmentally sensitive, or *open*, L-Systems.
+//!
+//! In an open L-System, plants can react to their surroundings, such as obstacles or
+//! the light available to them. After every derivation step, the string is interpreted by a
+//! turtle, and every *query symbol* (`?P`, see [`abop::QUERY`](crate::interpretation::abop::QUERY))
+//! has the turtle's position and heading recorded in a [`Query`]. An [`Environment`] is then
+//! given every query, and may replace the query symbol with another string. This follows
+//! Měch and Prusinkiewicz's *Visual models of plants interacting with their environment*.
+//!
+//! Replacing a query with the cut symbol (see [`System::with_cut_symbol`]) prunes the branch
+//! the query is on, which is what [`BoundingBoxEnvironment`] does for branches that grow
+//! outside of a box:
+//!
+//! ```
+//! use rusty_systems::prelude::*;
+//! use rusty_systems::geometry::BoundingBox;
+//! use rusty_systems::interpretation::abop::AbopTurtleInterpretation;
+//! use rusty_systems::system::environment::BoundingBoxEnvironment;
+//!
+//! let system = AbopTurtleInterpretation::system().unwrap();
+//! system.add_production("X -> Forward [ + ?P X ] [ - ?P X ]").unwrap();
+//!
+//! let bounds = BoundingBox { min_x: -8.0, max_x: 8.0, min_y: 0.0, max_y: 100.0, ..Default::default() };
+//! let environment = BoundingBoxEnvironment::build(bounds).unwrap();
+//!
+//! let turtle = AbopTurtleInterpretation::new(4, 90.0);
+//! let (string, queries) = system.derive_open("X".parse().unwrap(),
+//!                                            turtle.run_settings(),
+//!                                            &turtle,
+//!                                            Some(&environment)).unwrap();
+//!
+//! // Every remaining query is inside of the box.
+//! assert!(queries.iter().all(|q| environment.bounds().contains(q.position)));
+//! ```
+
+use std::fmt::Debug;
+
+use crate::geometry::{BoundingBox, Point, Vector};
+use crate::prelude::*;
+use crate::Result;
+
+/// The turtle's state when it reached a query symbol.
+#[derive(Debug, Copy, Clone)]
+pub struct Query {
+    /// The index of the query symbol in the string.
+    pub index: usize,
+    /// The query symbol.
+    pub symbol: Symbol,
+    /// Where the turtle was.
+    pub position: Point,
+    /// The direction the turtle was facing.
+    pub heading: Vector
+}
+
+/// An environment that an open L-System grows in. See the [module documentation](crate::system::environment).
+pub trait Environment: Debug {
+    /// Responds to a query. Returning a string replaces the query symbol with that string,
+    /// while [`None`] leaves the query symbol in place.
+    fn respond(&self, query: &Query) -> Option<ProductionString>;
+}
+
+/// Finds the query symbols of a string, and where each of them is. [`System::derive_open`]
+/// uses this after every derivation step.
+///
+/// The [ABOP turtle](crate::interpretation::abop::AbopTurtleInterpretation) locates queries by
+/// moving over the string. Closures taking the system and the string can also be used.
+pub trait QueryLocator {
+    /// Returns the queries of the string, ordered by [`Query::index`].
+    fn locate_queries(&self, system: &System, string: &ProductionString) -> Result<Vec<Query>>;
+}
+
+impl<F> QueryLocator for F
+    where F: Fn(&System, &ProductionString) -> Result<Vec<Query>>
+{
+    fn locate_queries(&self, system: &System, string: &ProductionString) -> Result<Vec<Query>> {
+        self(system, string)
+    }
+}
+
+/// An environment that prunes branches which grow outside of a bounding box, by replacing
+/// queries outside of the box with a cut symbol (`%` by default).
+///
+/// The system being derived should prune branches using the same cut symbol. See
+/// [`System::with_cut_symbol`].
+#[derive(Debug, Clone)]
+pub struct BoundingBoxEnvironment {
+    bounds: BoundingBox,
+    cut: Symbol
+}
+
+impl BoundingBoxEnvironment {
+    /// Creates an environment that prunes branches outside of the given bounds, using `%` as
+    /// the cut symbol.
+    pub fn build(bounds: BoundingBox) -> Result<Self> {
+        Ok(Self::with_cut(bounds, Symbol::build("%")?))
+    }
+
+    /// Creates an environment that prunes branches outside of the given bounds, using the
+    /// given cut symbol.
+    pub fn with_cut(bounds: BoundingBox, cut: Symbol) -> Self {
+        BoundingBoxEnvironment { bounds, cut }
+    }
+
+    /// The bounds that branches are allowed to grow in.
+    pub fn bounds(&self) -> &BoundingBox {
+        &self.bounds
+    }
+}
+
+impl Environment for BoundingBoxEnvironment {
+    fn respond(&self, query: &Query) -> Option<ProductionString> {
+        if self.bounds.contains(query.position) {
+            return None;
+        }
+
+        Some(ProductionString::from(self.cut))
+    }
+}
+
+/// Replaces the queried symbols in the string with the environment's responses.
+///
+/// The queries should be ordered by [`Query::index`].
+pub fn respond_to_queries(string: ProductionString,
+                          queries: &[Query],
+                          environment: &dyn Environment) -> ProductionString {
+    let mut responses = queries.iter()
+        .filter_map(|query| environment.respond(query).map(|response| (query.index, response)))
+        .peekable();
+
+    if responses.peek().is_none() {
+        return string;
+    }
+
+    let mut result = ProductionString::empty();
+    for (index, symbol) in string.into_iter().enumerate() {
+        match responses.next_if(|(i, _)| *i == index) {
+            Some((_, response)) => response.into_iter().for_each(|s| result.push_symbol(s)),
+            None => result.push_symbol(symbol)
+        }
+    }
+
+    result
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpretation::abop::AbopTurtleInterpretation;
+    use crate::parser::parse_prod_string;
+
+    #[test]
+    fn prunes_outside_of_bounds() {
+        let system = AbopTurtleInterpretation::system().unwrap();
+        system.add_production("X -> Forward ?P X").unwrap();
+
+        // The turtle starts at the origin and moves 5 units up for every Forward.
+        let bounds = BoundingBox { min_x: -1.0, max_x: 1.0, min_y: -1.0, max_y: 12.0, ..Default::default() };
+        let environment = BoundingBoxEnvironment::build(bounds).unwrap();
+        let turtle = AbopTurtleInterpretation::default();
+
+        let (string, queries) = system.derive_open(parse_prod_string("X").unwrap(),
+                                                   RunSettings::for_max_iterations(4),
+                                                   &turtle,
+                                                   Some(&environment)).unwrap();
+
+        assert_eq!(string, parse_prod_string("Forward ?P Forward ?P Forward").unwrap());
+        assert_eq!(queries.len(), 2);
+        assert_eq!(queries[0].index, 1);
+        assert_eq!(queries[1].position, Point::new(0.0, 10.0));
+    }
+
+    #[test]
+    fn queries_are_located_without_an_environment() {
+        let system = AbopTurtleInterpretation::system().unwrap();
+        system.add_production("X -> Forward [ + ?P ] X").unwrap();
+
+        let turtle = AbopTurtleInterpretation::new(2, 90.0);
+        let (string, queries) = system.derive_open(parse_prod_string("X").unwrap(),
+                                                   RunSettings::for_max_iterations(2),
+                                                   &turtle,
+                                                   None).unwrap();
+
+        assert_eq!(string.len(), 11);
+        assert_eq!(queries.len(), 2);
+        assert!((queries[0].heading.x() - -5.0).abs() < 0.0001);
+        assert_eq!(queries[1].position, Point::new(0.0, 10.0));
+    }
+
+    #[test]
+    fn closures_locate_queries() {
+        let system = System::new();
+        system.add_production("X -> Y X").unwrap();
+
+        // Every symbol is a query, located at its index along the x-axis.
+        let locator = |_: &System, string: &ProductionString| -> Result<Vec<Query>> {
+            Ok(string.iter().enumerate()
+                .map(|(index, symbol)| Query { index, symbol: *symbol, position: Point::new(index as f64, 0.0), heading: Vector::new(1.0, 0.0) })
+                .collect())
+        };
+
+        let bounds = BoundingBox { min_x: -1.0, max_x: 1.5, min_y: -1.0, max_y: 1.0, ..Default::default() };
+        let environment = BoundingBoxEnvironment::with_cut(bounds, Symbol::build("Cut").unwrap());
+        let (string, queries) = system.derive_open(parse_prod_string("X").unwrap(),
+                                                   RunSettings::for_max_iterations(2),
+                                                   &locator,
+                                                   Some(&environment)).unwrap();
+
+        // The system has no cut symbol, so the X outside of the box is only replaced.
+        assert_eq!(string, parse_prod_string("Y Y Cut").unwrap());
+        assert_eq!(queries.len(), 3);
+    }
+}
@@ -1,16 +1,62 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Debug;
 use std::sync::{Arc, OnceLock, RwLock};
 
 use crate::error::{Error, ErrorKind};
 use crate::Result;
 
+pub mod parser;
+
 pub struct Builder {
     terminals: Vec<SymbolDescription>,
-    productions: Vec<SymbolDescription>
+    productions: Vec<SymbolDescription>,
+    classes: Vec<(String, Vec<String>)>,
+    actions: Vec<(String, String)>,
+    parents: Vec<Parent>
+}
+
+/// A family that a [`Builder`] inherits from.
+enum Parent {
+    /// Looked up in the registry when the family is built.
+    Named(String),
+    Family(Box<SystemFamily>)
 }
 
 impl Builder {
+    /// Inherit all of the symbols and classes of the registered family with the given name.
+    ///
+    /// The parent is looked up when the family is built, and so only needs to be registered
+    /// by then. Families given to [`register_builtin`], such as `ABOP`, are registered on demand.
+    ///
+    /// ```
+    /// use rusty_systems::prelude::SystemFamily;
+    /// use rusty_systems::interpretation::abop;
+    ///
+    /// abop::register_family();
+    /// let family = SystemFamily::define()
+    ///     .extends("ABOP")
+    ///     .with_terminal("Leaf", Some("Draw a leaf"))
+    ///     .build("Leafy")
+    ///     .unwrap();
+    ///
+    /// assert!(family.is_terminal("Leaf"));
+    /// assert!(family.is_production("Forward"));
+    /// assert_eq!(family.parents(), ["ABOP"]);
+    /// ```
+    ///
+    /// Building fails if symbols or classes are declared differently by the family and its parents.
+    /// See [`SystemFamily::merge`].
+    pub fn extends<S: AsRef<str>>(mut self, name: S) -> Self {
+        self.parents.push(Parent::Named(name.as_ref().to_string()));
+        self
+    }
+
+    /// Inherit all of the symbols and classes of the given family. See [`Builder::extends`].
+    pub fn extend(mut self, family: &SystemFamily) -> Self {
+        self.parents.push(Parent::Family(Box::new(family.clone())));
+        self
+    }
+
     /// Register a terminal, with an optional description of what that terminal represents.
     ///
     /// This does *not* create terminals (see, for instance, [`System`](crate::prelude::System)),
@@ -26,7 +72,8 @@ impl Builder {
     pub fn with_terminal<S: AsRef<str>>(mut self, name: S, description: Option<S>) -> Self {
         let symbol = SymbolDescription {
             name: name.as_ref().to_string(),
-            description: description.map(|s| s.as_ref().to_string())
+            description: description.map(|s| s.as_ref().to_string()),
+            action: None
         };
         self.terminals.push(symbol);
         self
@@ -47,12 +94,53 @@ impl Builder {
     pub fn with_production<S: AsRef<str>>(mut self, name: S, description: Option<S>) -> Self {
         let symbols = SymbolDescription {
             name: name.as_ref().to_string(),
-            description: description.map(|s| s.as_ref().to_string())
+            description: description.map(|s| s.as_ref().to_string()),
+            action: None
         };
         self.productions.push(symbols);
         self
     }
 
+    /// Sets the action that an interpretation should perform for a symbol, such as moving a turtle
+    /// forward. The symbol must be a terminal or production of the family.
+    ///
+    /// Which actions are understood depends on the interpretation.
+    /// See [`TurtleAction`](crate::interpretation::abop::TurtleAction).
+    ///
+    /// For example:
+    /// ```
+    /// use rusty_systems::prelude::SystemFamily;
+    /// let family = SystemFamily::define()
+    ///     .with_production("F", Some("Draw a line"))
+    ///     .with_action("F", "forward")
+    ///     .build("Short")
+    ///     .unwrap();
+    /// assert_eq!(family.symbol("F").unwrap().action.as_deref(), Some("forward"));
+    /// ```
+    pub fn with_action<S: AsRef<str>>(mut self, symbol: S, action: S) -> Self {
+        self.actions.push((symbol.as_ref().to_string(), action.as_ref().to_string()));
+        self
+    }
+
+    /// Register a named class of symbols, which productions can match in their contexts
+    /// by referring to the class in braces. See the [productions module](crate::productions).
+    ///
+    /// The members must be terminals or productions of the family.
+    ///
+    /// For example:
+    /// ```
+    /// use rusty_systems::prelude::SystemFamily;
+    /// SystemFamily::define()
+    ///     .with_terminal("L1", None)
+    ///     .with_terminal("L2", None)
+    ///     .with_class("Leaf", &["L1", "L2"]);
+    /// ```
+    pub fn with_class<S: AsRef<str>>(mut self, name: S, members: &[S]) -> Self {
+        let members = members.iter().map(|m| m.as_ref().to_string()).collect();
+        self.classes.push((name.as_ref().to_string(), members));
+        self
+    }
+
     /// Registers the new [`SystemFamily`] and returns a pointer to it.
     ///
     /// Fails if the [`SystemFamily`] is malformed, or if the chosen name
@@ -61,13 +149,20 @@ impl Builder {
     pub fn register<S: AsRef<str>>(self, name: S) -> Result<Arc<SystemFamily>> {
         let name = name.as_ref();
 
+        if reference().read()?.contains_key(name) {
+            return Err(Error::new(ErrorKind::Duplicate,
+                                  format!("family name [{}] is already taken", name)));
+        }
+
+        // Building may look up parents in the registry, so must happen before it is locked for writing.
+        let family = self.build(name)?;
+
         let mut map = reference().write()?;
         if map.contains_key(name) {
             return Err(Error::new(ErrorKind::Duplicate,
                                   format!("family name [{}] is already taken", name)));
         }
 
-        let family = self.build(name)?;
         map.insert(name.to_string(), Arc::new(family));
         Ok(map.get(name).unwrap().clone())
     }
@@ -81,11 +176,60 @@ impl Builder {
             return Err(Error::new(ErrorKind::Definitions, "family name cannot be empty"));
         }
 
-        Ok(SystemFamily {
+        let mut family = SystemFamily {
             name: name.to_string(),
-            terminals: self.terminals.into_iter().collect(),
-            productions: self.productions.into_iter().collect()
-        })
+            terminals: BTreeMap::new(),
+            productions: BTreeMap::new(),
+            classes: BTreeMap::new(),
+            parents: Vec::new()
+        };
+
+        for parent in self.parents {
+            let parent = match parent {
+                Parent::Named(parent) => get_family(&parent).ok_or_else(||
+                    Error::definition(format!("family [{name}] extends [{parent}], which has not been registered")))?,
+                Parent::Family(parent) => Arc::new(*parent)
+            };
+
+            family.absorb(&parent)?;
+            family.parents.push(parent.name().clone());
+        }
+
+        for terminal in self.terminals {
+            family.declare(terminal, true)?;
+        }
+
+        for production in self.productions {
+            family.declare(production, false)?;
+        }
+
+        for (symbol, action) in self.actions {
+            let description = family.symbol(&symbol)
+                .ok_or_else(|| Error::definition(format!("an action is given for [{symbol}], which is not a symbol of the family")))?;
+
+            let terminal = family.is_terminal(&symbol);
+            family.declare(SymbolDescription { action: Some(action), ..description.clone() }, terminal)?;
+        }
+
+        let mut declared: HashMap<String, Vec<String>> = HashMap::new();
+        for (class, members) in self.classes {
+            if class.trim().is_empty() || class.contains(|c: char| c.is_whitespace() || c == '{' || c == '}') {
+                return Err(Error::definition(format!("[{class}] is not a valid class name")));
+            }
+
+            if let Some(member) = members.iter().find(|m| !family.contains(m)) {
+                return Err(Error::definition(
+                    format!("class [{class}] has the member [{member}], which is not a symbol of the family")));
+            }
+
+            if declared.insert(class.clone(), members.clone()).is_some() {
+                return Err(Error::new(ErrorKind::Duplicate, format!("class [{class}] is defined more than once")));
+            }
+
+            family.declare_class(class, members)?;
+        }
+
+        Ok(family)
     }
 
 }
@@ -93,14 +237,108 @@ impl Builder {
 #[derive(Debug, Clone)]
 pub struct SystemFamily {
     name: String,
-    terminals: HashMap<String, SymbolDescription>,
-    productions: HashMap<String, SymbolDescription>
+    terminals: BTreeMap<String, SymbolDescription>,
+    productions: BTreeMap<String, SymbolDescription>,
+    classes: BTreeMap<String, Vec<String>>,
+    parents: Vec<String>
 }
 
 impl SystemFamily {
     /// Define a family of [`System`](crate::prelude::System) instances.
     pub fn define() -> Builder {
-        Builder { terminals: Vec::new(), productions: Vec::new() }
+        Builder {
+            terminals: Vec::new(),
+            productions: Vec::new(),
+            classes: Vec::new(),
+            actions: Vec::new(),
+            parents: Vec::new()
+        }
+    }
+
+    /// Creates a new family with the given name, having the symbols and classes of both
+    /// this family and `other`.
+    ///
+    /// This fails with an [`ErrorKind::Definitions`] error if the families conflict: if they both
+    /// have a symbol of the same name, but of different kinds or with different descriptions or actions, or a class of
+    /// the same name with different members. A symbol with no description does not conflict with
+    /// one that has a description.
+    pub fn merge<S: AsRef<str>>(&self, other: &SystemFamily, name: S) -> Result<SystemFamily> {
+        SystemFamily::define()
+            .extend(self)
+            .extend(other)
+            .build(name)
+    }
+
+    /// The names of the families that this family was built from.
+    /// See [`Builder::extends`] and [`Builder::extend`].
+    pub fn parents(&self) -> &[String] {
+        &self.parents
+    }
+
+    /// Adds all the symbols and classes of `other` to this family.
+    fn absorb(&mut self, other: &SystemFamily) -> Result<()> {
+        for terminal in other.terminals() {
+            self.declare(terminal.clone(), true)?;
+        }
+
+        for production in other.productions() {
+            self.declare(production.clone(), false)?;
+        }
+
+        for (class, members) in other.classes() {
+            self.declare_class(class.clone(), members.to_vec())?;
+        }
+
+        Ok(())
+    }
+
+    fn declare(&mut self, symbol: SymbolDescription, terminal: bool) -> Result<()> {
+        let (same, other) = match terminal {
+            true => (&mut self.terminals, &self.productions),
+            false => (&mut self.productions, &self.terminals)
+        };
+
+        if other.contains_key(&symbol.name) {
+            return Err(Error::definition(
+                format!("[{}] is declared as both a terminal and a production in family [{}]", symbol.name, self.name)));
+        }
+
+        let existing = match same.get_mut(&symbol.name) {
+            None => {
+                same.insert(symbol.name.clone(), symbol);
+                return Ok(());
+            }
+            Some(existing) => existing
+        };
+
+        match (&existing.description, symbol.description) {
+            (_, None) => {}
+            (None, description) => existing.description = description,
+            (Some(a), Some(b)) if *a == b => {}
+            (Some(_), Some(_)) => return Err(Error::definition(
+                format!("[{}] is declared with different descriptions in family [{}]", symbol.name, self.name)))
+        }
+
+        match (&existing.action, symbol.action) {
+            (_, None) => {}
+            (None, action) => existing.action = action,
+            (Some(a), Some(b)) if *a == b => {}
+            (Some(_), Some(_)) => return Err(Error::definition(
+                format!("[{}] is given different actions in family [{}]", symbol.name, self.name)))
+        }
+
+        Ok(())
+    }
+
+    fn declare_class(&mut self, class: String, members: Vec<String>) -> Result<()> {
+        match self.classes.get(&class) {
+            Some(existing) if *existing != members => Err(Error::definition(
+                format!("class [{class}] is declared with different members in family [{}]", self.name))),
+            _ => {
+                self.classes.insert(class, members);
+                Ok(())
+            }
+        }
     }
 
     /// Returns the name of the [`SystemFamily`]
@@ -108,12 +346,12 @@ impl SystemFamily {
         &self.name
     }
 
-    /// Returns an iterator over all of the terminals registered for this family.
+    /// Returns an iterator over all of the terminals registered for this family, ordered by name.
     pub fn terminals(&self) -> impl Iterator<Item=&SymbolDescription> {
         self.terminals.values()
     }
 
-    /// Returns an iterator over all of the Productions registered for this family.
+    /// Returns an iterator over all of the Productions registered for this family, ordered by name.
     pub fn productions(&self) -> impl Iterator<Item=&SymbolDescription> {
         self.productions.values()
     }
@@ -122,6 +360,40 @@ impl SystemFamily {
     pub fn symbols(&self) -> impl Iterator<Item=&SymbolDescription> {
         self.terminals().chain(self.productions())
     }
+
+    /// Returns true iff the family has a terminal or production with the given name.
+    pub fn contains<S: AsRef<str>>(&self, name: S) -> bool {
+        let name = name.as_ref();
+        self.terminals.contains_key(name) || self.productions.contains_key(name)
+    }
+
+    /// Returns the description of the terminal or production with the given name.
+    pub fn symbol<S: AsRef<str>>(&self, name: S) -> Option<&SymbolDescription> {
+        let name = name.as_ref();
+        self.terminals.get(name).or_else(|| self.productions.get(name))
+    }
+
+    /// Returns true iff the family declares a terminal with the given name.
+    pub fn is_terminal<S: AsRef<str>>(&self, name: S) -> bool {
+        self.terminals.contains_key(name.as_ref())
+    }
+
+    /// Returns true iff the family declares a production symbol with the given name.
+    pub fn is_production<S: AsRef<str>>(&self, name: S) -> bool {
+        self.productions.contains_key(name.as_ref())
+    }
+
+    /// Returns the names of the members of the given symbol class, if the family declares it.
+    /// See [`Builder::with_class`].
+    pub fn class<S: AsRef<str>>(&self, name: S) -> Option<&[String]> {
+        self.classes.get(name.as_ref()).map(|members| members.as_slice())
+    }
+
+    /// Returns an iterator over the names of the family's symbol classes, along with their members,
+    /// ordered by the class names.
+    pub fn classes(&self) -> impl Iterator<Item=(&String, &[String])> {
+        self.classes.iter().map(|(name, members)| (name, members.as_slice()))
+    }
 }
 
 
@@ -129,9 +401,43 @@ impl SystemFamily {
 ///
 /// Note: if synchronisation locks are poisoned this will return [`None`],
 /// as though there was no family registered.
+///
+/// Families given to [`register_builtin`], such as `ABOP`, are registered the first time
+/// that they are asked for.
 pub fn get_family<S: AsRef<str>>(name: S) -> Option<Arc<SystemFamily>> {
-    let map = reference().read().ok()?;
-    map.get(name.as_ref()).cloned()
+    let name = name.as_ref();
+    let found = reference().read().ok()?.get(name).cloned();
+
+    match found {
+        Some(family) => Some(family),
+        None => {
+            let default = *builtins().read().ok()?.get(name)?;
+            Some(get_or_init_family(name, default))
+        }
+    }
+}
+
+/// Makes a family available under the given name, without building it. The family is built
+/// and registered the first time that it is asked for, such as by [`get_family`] or by a family
+/// that [extends](Builder::extends) it.
+///
+/// Registering the same name again replaces the function used to build it, if the family has not
+/// yet been built.
+///
+/// ```
+/// use rusty_systems::system::family;
+/// use rusty_systems::prelude::SystemFamily;
+///
+/// family::register_builtin("Mosses", || SystemFamily::define().with_terminal("Spore", None).build("Mosses").unwrap());
+/// assert!(family::family_names().contains(&String::from("Mosses")));
+/// assert!(family::get_family("Mosses").unwrap().is_terminal("Spore"));
+/// ```
+///
+/// The ABOP family is made available by [`abop::register_family`](crate::interpretation::abop::register_family).
+pub fn register_builtin<S: AsRef<str>>(name: S, default: fn() -> SystemFamily) {
+    if let Ok(mut map) = builtins().write() {
+        map.insert(name.as_ref().to_string(), default);
+    }
 }
 
 /// Examples
@@ -146,24 +452,20 @@ pub fn get_or_init_family<S, F>(name: S, default: F) -> Arc<SystemFamily>
           F: FnOnce() -> SystemFamily
 {
     // See if we have a value using just a read lock.
-    if let Some(value) = get_family(name.as_ref()) {
-        return value;
-    }
-
-    let mut map = reference().write().unwrap();
-    // Now we have a write lock. Need to double-check that the family
-    // hasn't been registered
-    if let Some(value) = map.get(name.as_ref()) {
+    if let Some(value) = reference().read().unwrap().get(name.as_ref()) {
         return value.clone();
     }
 
-    // Build, but now synchronised via the above write guard.
-    let family = default();
-
-    let family = Arc::new(family);
-    map.insert(name.as_ref().to_string(), family.clone());
+    // Build before taking the write lock, since families may look up their parents
+    // in the registry while being built.
+    let family = Arc::new(default());
 
-    family
+    let mut map = reference().write().unwrap();
+    // Now we have a write lock. Need to double-check that the family
+    // hasn't been registered by another thread in the meantime.
+    map.entry(name.as_ref().to_string())
+        .or_insert(family)
+        .clone()
 }
 
 /// Returns true if and only if a family with the given name has been registered.
@@ -174,12 +476,44 @@ pub fn family_exists<S: AsRef<str>>(name: S) -> bool {
     get_family(name).is_some()
 }
 
+/// Returns the names of every registered family, in order. This includes the families given
+/// to [`register_builtin`], such as `ABOP`, even if they have not yet been asked for.
+///
+/// ```
+/// use rusty_systems::system::family;
+/// use rusty_systems::interpretation::abop;
+///
+/// abop::register_family();
+/// assert!(family::family_names().contains(&String::from("ABOP")));
+/// ```
+pub fn family_names() -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    if let Ok(map) = builtins().read() {
+        names.extend(map.keys().cloned());
+    }
+    if let Ok(map) = reference().read() {
+        names.extend(map.keys().cloned());
+    }
+
+    names.sort();
+    names.dedup();
+    names
+}
+
 /// Private. This returns a pointer to the family registry.
 fn reference() -> &'static RwLock<HashMap<String, Arc<SystemFamily>>> {
     static REGISTRY : OnceLock<RwLock<HashMap<String, Arc<SystemFamily>>>> = OnceLock::new();
     REGISTRY.get_or_init(|| RwLock::new(HashMap::new()))
 }
 
+type BuiltinRegistry = RwLock<HashMap<String, fn() -> SystemFamily>>;
+
+/// Private. The families that are built when first asked for. See [`register_builtin`].
+fn builtins() -> &'static BuiltinRegistry {
+    static BUILTINS : OnceLock<BuiltinRegistry> = OnceLock::new();
+    BUILTINS.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
 pub fn register(family: SystemFamily) -> Result<Arc<SystemFamily>> {
     let name = family.name().clone();
     let mut map = reference().write()?;
@@ -228,7 +562,9 @@ pub struct SymbolDescription {
     /// The symbol's name
     pub name: String,
     /// What this symbol represents.
-    pub description: Option<String>
+    pub description: Option<String>,
+    /// The action an interpretation performs for this symbol, if any. See [`Builder::with_action`].
+    pub action: Option<String>
 }
 
 impl FromIterator<SymbolDescription> for HashMap<String, SymbolDescription> {
@@ -247,7 +583,6 @@ impl FromIterator<SymbolDescription> for HashMap<String, SymbolDescription> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::interpretation::abop;
 
     #[test]
     fn empty_name() {
@@ -279,8 +614,120 @@ mod tests {
     }
 
     #[test]
-    fn abop_available() {
-        let abop = get_or_init_family("ABOP", abop::abop_family);
-        assert_eq!(abop.name(), "ABOP");
+    fn classes_must_have_declared_members() {
+        let family = SystemFamily::define()
+            .with_terminal("L1", None)
+            .with_terminal("L2", None)
+            .with_class("Leaf", &["L1", "L2"])
+            .build("Classes")
+            .unwrap();
+        assert_eq!(family.class("Leaf").unwrap(), ["L1", "L2"]);
+        assert!(family.class("Stem").is_none());
+
+        let result = SystemFamily::define()
+            .with_terminal("L1", None)
+            .with_class("Leaf", &["L1", "L3"])
+            .build("Classes");
+        assert!(result.is_err());
+
+        let result = SystemFamily::define()
+            .with_terminal("L1", None)
+            .with_class("{Leaf}", &["L1"])
+            .build("Classes");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn extends_families() {
+        let base = SystemFamily::define()
+            .with_terminal("a", Some("The letter a"))
+            .with_production("S", None)
+            .with_class("Letters", &["a"])
+            .build("Base")
+            .unwrap();
+
+        let child = SystemFamily::define()
+            .extend(&base)
+            .with_terminal("a", None)
+            .with_terminal("b", None)
+            .with_production("S", Some("The start symbol"))
+            .build("Child")
+            .unwrap();
+        assert!(child.is_terminal("a") && child.is_terminal("b"));
+        assert_eq!(child.terminals.get("a").unwrap().description.as_deref(), Some("The letter a"));
+        assert_eq!(child.productions.get("S").unwrap().description.as_deref(), Some("The start symbol"));
+        assert_eq!(child.class("Letters").unwrap(), ["a"]);
+        assert_eq!(child.parents(), ["Base"]);
+
+        // Families are only looked up when built.
+        let builder = SystemFamily::define().extends("LazyParent").with_terminal("c", None);
+        SystemFamily::define().with_terminal("d", None).register("LazyParent").unwrap();
+        let lazy = builder.build("LazyChild").unwrap();
+        assert!(lazy.contains("c") && lazy.contains("d"));
+
+        assert!(SystemFamily::define().extends("NotRegistered").build("Orphan").is_err());
+    }
+
+    #[test]
+    fn merge_detects_conflicts() {
+        let first = SystemFamily::define()
+            .with_terminal("a", Some("The letter a"))
+            .with_production("S", None)
+            .build("First")
+            .unwrap();
+
+        let compatible = SystemFamily::define()
+            .with_terminal("a", None)
+            .with_terminal("b", None)
+            .build("Compatible")
+            .unwrap();
+        let merged = first.merge(&compatible, "Merged").unwrap();
+        assert_eq!(merged.symbols().count(), 3);
+        assert_eq!(merged.parents(), ["First", "Compatible"]);
+
+        let kind = SystemFamily::define().with_production("a", None).build("Kind").unwrap();
+        let error = first.merge(&kind, "Merged").unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::Definitions));
+
+        let description = SystemFamily::define().with_terminal("a", Some("Not a")).build("Description").unwrap();
+        assert!(first.merge(&description, "Merged").is_err());
+
+        let classes = |members: &[&str]| SystemFamily::define()
+            .with_terminal("a", None)
+            .with_terminal("b", None)
+            .with_class("Letters", members)
+            .build("Classes")
+            .unwrap();
+        assert!(classes(&["a"]).merge(&classes(&["a"]), "Merged").is_ok());
+        assert!(classes(&["a"]).merge(&classes(&["a", "b"]), "Merged").is_err());
+    }
+
+    #[test]
+    fn builtins_are_built_on_demand() {
+        register_builtin("OnDemand", || SystemFamily::define().with_terminal("a", None).build("OnDemand").unwrap());
+        assert!(reference().read().unwrap().get("OnDemand").is_none());
+        assert!(family_names().contains(&String::from("OnDemand")));
+
+        let family = SystemFamily::define().extends("OnDemand").build("OnDemandChild").unwrap();
+        assert!(family.is_terminal("a"));
+        assert!(reference().read().unwrap().get("OnDemand").is_some());
+    }
+
+    #[test]
+    fn listed_in_order() {
+        SystemFamily::define()
+            .with_terminal("c", None)
+            .with_terminal("a", None)
+            .with_terminal("b", None)
+            .register("Ordered")
+            .unwrap();
+
+        let names = family_names();
+        assert!(names.contains(&String::from("Ordered")));
+        assert!(names.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let family = get_family("Ordered").unwrap();
+        let terminals: Vec<&str> = family.terminals().map(|t| t.name.as_str()).collect();
+        assert_eq!(terminals, ["a", "b", "c"]);
     }
 }
\ No newline at end of file
@@ -0,0 +1,276 @@
+//! Reading [`SystemFamily`] definitions from text.
+//!
+//! # The format
+//!
+//! ```family
+//! # Comments start with a hash at the start of a line, or after whitespace.
+//! # A hash that is part of a name or description is written as \#.
+//! name: Leafy             # The name the family is registered under
+//! extends: ABOP           # Optional, and may be repeated
+//!
+//! # Symbols are declared with an optional turtle action and description:
+//! #   terminal <name> [= <action>] [: <description>]
+//! #   production <name> [= <action>] [: <description>]
+//! terminal Leaf = forward : Draws a leaf
+//! terminal \# : Makes the line wider
+//! terminal Bud : Where new branches will grow
+//! production Stem = forward : Grows into a branch
+//!
+//! # Classes of symbols, for use in production contexts
+//! class Green = Leaf Stem
+//! ```
+//!
+//! The actions understood by the turtle are listed in
+//! [`TurtleAction`](crate::interpretation::abop::TurtleAction).
+//!
+//! # Loading
+//!
+//! [`parse`] and [`parse_file`] create a family without registering it, while [`load`] and
+//! [`load_file`] also add it to the family registry, so that it can be found with
+//! [`get_family`](crate::system::family::get_family).
+//!
+//! ```
+//! use rusty_systems::system::family::parser::load;
+//! use rusty_systems::system::family::get_family;
+//! use rusty_systems::interpretation::abop;
+//!
+//! abop::register_family();
+//! let family = load("name: Ferns\nextends: ABOP\nterminal Frond = forward : A fern leaf").unwrap();
+//! assert!(family.is_terminal("Frond"));
+//! assert!(family.is_production("Forward"));
+//! assert!(get_family("Ferns").is_some());
+//! ```
+
+use std::sync::Arc;
+
+use crate::error::{Error, ErrorKind};
+use crate::Result;
+use super::{register, SystemFamily};
+
+/// Parses a family definition, without registering the family.
+/// See the [module documentation](crate::system::family::parser) for the format.
+///
+/// Errors are of kind [`ErrorKind::Parse`] when the text is malformed, and otherwise are those
+/// of [`Builder::build`](super::Builder::build).
+pub fn parse(string: &str) -> Result<SystemFamily> {
+    let mut name: Option<String> = None;
+    let mut builder = SystemFamily::define();
+
+    for (number, line) in string.lines().enumerate() {
+        let number = number + 1;
+        let line = remove_comment(line);
+        let line = line.as_str();
+        if line.is_empty() {
+            continue;
+        }
+
+        let (keyword, rest) = line.split_once(char::is_whitespace)
+            .map(|(keyword, rest)| (keyword, rest.trim()))
+            .unwrap_or((line, ""));
+
+        builder = match keyword {
+            "name:" => {
+                if name.replace(non_empty(rest, number, "a name")?.to_string()).is_some() {
+                    return Err(line_error(number, "the family has already been given a name"));
+                }
+                builder
+            }
+            "extends:" => builder.extends(non_empty(rest, number, "the name of a family")?),
+            "terminal" => {
+                let (symbol, action, description) = parse_symbol(rest, number)?;
+                let builder = builder.with_terminal(symbol, description);
+                match action {
+                    Some(action) => builder.with_action(symbol, action),
+                    None => builder
+                }
+            }
+            "production" => {
+                let (symbol, action, description) = parse_symbol(rest, number)?;
+                let builder = builder.with_production(symbol, description);
+                match action {
+                    Some(action) => builder.with_action(symbol, action),
+                    None => builder
+                }
+            }
+            "class" => {
+                let (class, members) = rest.split_once('=')
+                    .ok_or_else(|| line_error(number, "a class should be written as: class <name> = <members>"))?;
+                let members: Vec<&str> = members.split_whitespace().collect();
+                if members.is_empty() {
+                    return Err(line_error(number, "a class needs at least one member"));
+                }
+                builder.with_class(non_empty(class.trim(), number, "a class name")?, &members)
+            }
+            _ => return Err(line_error(number, format!("unrecognised line: {line}")))
+        };
+    }
+
+    let name = name.ok_or_else(|| Error::new(ErrorKind::Parse, "the family has not been given a name"))?;
+    builder.build(name)
+}
+
+/// Reads a file containing a family definition, without registering the family. See [`parse`].
+pub fn parse_file<P: AsRef<std::path::Path>>(path: P) -> Result<SystemFamily> {
+    let contents = std::fs::read_to_string(path)?;
+    parse(&contents)
+}
+
+/// Parses a family definition and registers the family under its name.
+///
+/// Fails with [`ErrorKind::Duplicate`] if a family of that name has already been registered.
+/// See [`parse`].
+pub fn load(string: &str) -> Result<Arc<SystemFamily>> {
+    register(parse(string)?)
+}
+
+/// Reads a file containing a family definition and registers the family. See [`load`].
+pub fn load_file<P: AsRef<std::path::Path>>(path: P) -> Result<Arc<SystemFamily>> {
+    register(parse_file(path)?)
+}
+
+
+/// Splits `<name> [= <action>] [: <description>]` into its parts.
+fn parse_symbol(line: &str, number: usize) -> Result<(&str, Option<&str>, Option<&str>)> {
+    let (rest, description) = match line.split_once(':') {
+        Some((rest, description)) => (rest, Some(non_empty(description.trim(), number, "a description")?)),
+        None => (line, None)
+    };
+
+    let (symbol, action) = match rest.split_once('=') {
+        Some((symbol, action)) => (symbol.trim(), Some(non_empty(action.trim(), number, "an action")?)),
+        None => (rest.trim(), None)
+    };
+
+    if symbol.is_empty() || symbol.contains(char::is_whitespace) {
+        return Err(line_error(number, format!("[{symbol}] is not a valid symbol name")));
+    }
+
+    Ok((symbol, action, description))
+}
+
+fn non_empty<'a>(value: &'a str, number: usize, expected: &str) -> Result<&'a str> {
+    match value.is_empty() {
+        true => Err(line_error(number, format!("expected {expected}"))),
+        false => Ok(value)
+    }
+}
+
+fn line_error<S: AsRef<str>>(number: usize, message: S) -> Error {
+    Error::new(ErrorKind::Parse, format!("line {number}: {}", message.as_ref()))
+}
+
+/// Removes any comment from the line, and replaces each escaped `\#` with `#`. Comments start
+/// with a `#` at the start of the line or following whitespace.
+fn remove_comment(line: &str) -> String {
+    let mut result = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    // Whether the next character starts the line or follows whitespace.
+    let mut after_space = true;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'#') => {
+                result.push('#');
+                chars.next();
+                after_space = false;
+                continue;
+            }
+            '#' if after_space => break,
+            _ => result.push(c)
+        }
+        after_space = c.is_whitespace();
+    }
+
+    result.trim().to_string()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::family::get_or_init_family;
+
+    static LEAFY: &str = "# A family for testing
+name: Leafy
+extends: Stems
+
+terminal Leaf = forward : Draws a leaf
+terminal Bud : Where branches grow   # Has no action
+production Stem = forward
+class Green = Leaf Stem
+";
+
+    /// Registers a family for the families being parsed to extend.
+    fn register_stems() {
+        get_or_init_family("Stems", || SystemFamily::define()
+            .with_production("Forward", Some("Grows"))
+            .with_action("Forward", "forward")
+            .build("Stems")
+            .unwrap());
+    }
+
+    #[test]
+    fn parses_families() {
+        register_stems();
+        let family = parse(LEAFY).unwrap();
+
+        assert_eq!(family.name(), "Leafy");
+        assert_eq!(family.parents(), ["Stems"]);
+        assert!(family.is_production("Forward"));
+
+        let leaf = family.symbol("Leaf").unwrap();
+        assert!(family.is_terminal("Leaf"));
+        assert_eq!(leaf.action.as_deref(), Some("forward"));
+        assert_eq!(leaf.description.as_deref(), Some("Draws a leaf"));
+
+        let bud = family.symbol("Bud").unwrap();
+        assert_eq!(bud.action, None);
+        assert_eq!(bud.description.as_deref(), Some("Where branches grow"));
+
+        let stem = family.symbol("Stem").unwrap();
+        assert!(family.is_production("Stem"));
+        assert_eq!(stem.action.as_deref(), Some("forward"));
+        assert_eq!(stem.description, None);
+
+        assert_eq!(family.class("Green").unwrap(), ["Leaf", "Stem"]);
+    }
+
+    #[test]
+    fn hashes_in_names_and_descriptions() {
+        let family = parse("name: Hashes  # The family's name\n\
+                            terminal \\# = scale-width : Makes the line wider  # A comment\n\
+                            terminal Bud#").unwrap();
+
+        let hash = family.symbol("#").unwrap();
+        assert_eq!(hash.action.as_deref(), Some("scale-width"));
+        assert_eq!(hash.description.as_deref(), Some("Makes the line wider"));
+        assert!(family.is_terminal("Bud#"));
+
+        let family = parse("name: Hashes\nterminal Leaf : Draws leaf \\#1, as in C#").unwrap();
+        assert_eq!(family.symbol("Leaf").unwrap().description.as_deref(), Some("Draws leaf #1, as in C#"));
+    }
+
+    #[test]
+    fn reports_line_numbers() {
+        let error = parse("name: Broken\n\nterminal = forward").unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::Parse));
+        assert!(error.to_string().contains("line 3"));
+
+        let error = parse("name: Broken\nleaf Leaf").unwrap_err();
+        assert!(error.to_string().contains("line 2"));
+
+        assert!(matches!(parse("terminal Leaf").unwrap_err().kind(), ErrorKind::Parse));
+        assert!(matches!(parse("name: A\nname: B").unwrap_err().kind(), ErrorKind::Parse));
+        assert!(matches!(parse("name: A\nclass Green =").unwrap_err().kind(), ErrorKind::Parse));
+    }
+
+    #[test]
+    fn conflicts_with_parents() {
+        register_stems();
+        let error = parse("name: Clash\nextends: Stems\nterminal Forward").unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::Definitions));
+
+        let error = parse("name: Clash\nterminal Leaf = forward\nterminal Leaf = move").unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::Definitions));
+    }
+}
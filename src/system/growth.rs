@@ -0,0 +1,347 @@
+//! Predicting how strings grow when derived by deterministic, context-free systems.
+//!
+//! For a deterministic, context-free L-System (a *D0L-system*), the number of each symbol
+//! after a derivation step depends only on the number of each symbol before the step.
+//! This relationship is captured by the system's *growth matrix*, whose rows
+//! give the number of each symbol that a symbol is rewritten to. See chapter 1.6 of
+//! [the Algorithmic Beauty of Plants][abop].
+//!
+//! Multiplying the symbol counts of a string (its *Parikh vector*) by this matrix predicts
+//! the symbol counts of the next generation, without having to derive the string itself.
+//!
+//! ```
+//! use rusty_systems::prelude::*;
+//!
+//! let system = System::new();
+//! system.add_production("A -> A B").unwrap();
+//! system.add_production("B -> A").unwrap();
+//!
+//! let growth = system.growth_matrix().unwrap();
+//! let axiom = "B".parse().unwrap();
+//!
+//! // The lengths follow the fibonacci sequence
+//! assert_eq!(growth.length(&axiom, 10), 89);
+//! assert!((growth.growth_rate() - 1.618).abs() < 0.001);
+//! ```
+//!
+//! [abop]: http://algorithmicbotany.org/papers/#abop
+
+use std::collections::HashMap;
+
+use crate::error::Error;
+use crate::prelude::*;
+use crate::productions::Production;
+use crate::symbols::iterator::SymbolIterable;
+use crate::Result;
+
+/// The growth matrix of a deterministic, context-free system. See the
+/// [module documentation](crate::system::growth).
+///
+/// Predicted counts saturate at [`u64::MAX`] rather than overflowing.
+#[derive(Debug, Clone)]
+pub struct GrowthMatrix {
+    symbols: Vec<Symbol>,
+    indices: HashMap<Symbol, usize>,
+    /// `matrix[i][j]` is the number of `symbols[j]` produced from `symbols[i]`.
+    matrix: Vec<Vec<u64>>
+}
+
+impl GrowthMatrix {
+    /// Builds the growth matrix for the given productions.
+    ///
+    /// Returns an [`ErrorKind::Definitions`](crate::error::ErrorKind::Definitions) error if any of the
+    /// productions are context-sensitive, stochastic, or rewrite sequences of symbols, since the growth of
+    /// such systems cannot be predicted from symbol counts alone.
+    pub fn build(productions: &[Production]) -> Result<Self> {
+        let mut chosen: HashMap<Symbol, &Production> = HashMap::new();
+
+        for production in productions {
+            let head = production.head();
+            if head.is_sequence() {
+                return Err(Error::definition(
+                    format!("growth can only be predicted for productions of single symbols, but found [{head}]")));
+            }
+
+            if head.specificity() > 0 {
+                return Err(Error::definition(
+                    format!("growth can only be predicted for context-free productions, but found [{head}]")));
+            }
+
+            if production.all_bodies().len() != 1 {
+                return Err(Error::definition(
+                    format!("growth can only be predicted for deterministic productions, but [{head}] is stochastic")));
+            }
+
+            // Mirror find_matching: the first production of the highest priority wins.
+            match chosen.get(head.target()) {
+                Some(current) if !production.precedence_cmp(current).is_gt() => {}
+                _ => { chosen.insert(*head.target(), production); }
+            }
+        }
+
+        let mut symbols: Vec<Symbol> = Vec::new();
+        let mut indices: HashMap<Symbol, usize> = HashMap::new();
+        for symbol in productions.iter().flat_map(|p| p.all_symbols_iter()) {
+            indices.entry(symbol).or_insert_with(|| {
+                symbols.push(symbol);
+                symbols.len() - 1
+            });
+        }
+
+        let size = symbols.len();
+        let mut matrix = vec![vec![0_u64; size]; size];
+
+        for (i, symbol) in symbols.iter().enumerate() {
+            match chosen.get(symbol) {
+                // Symbols without productions are copied unchanged.
+                None => matrix[i][i] = 1,
+                Some(production) => {
+                    for successor in production.body()?.string() {
+                        let j = indices[&successor];
+                        matrix[i][j] = matrix[i][j].saturating_add(1);
+                    }
+                }
+            }
+        }
+
+        Ok(GrowthMatrix { symbols, indices, matrix })
+    }
+
+    /// The symbols that the rows and columns of the matrix refer to, in order.
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    /// Returns the number of `to` symbols that a single `from` symbol is
+    /// rewritten to in one derivation step.
+    pub fn get(&self, from: Symbol, to: Symbol) -> u64 {
+        match (self.indices.get(&from), self.indices.get(&to)) {
+            (Some(i), Some(j)) => self.matrix[*i][*j],
+            _ if from == to => 1,
+            _ => 0
+        }
+    }
+
+    /// Predicts the number of each symbol in the string produced by deriving
+    /// `axiom` for the given number of iterations. Symbols that do not occur are not returned.
+    pub fn symbol_counts(&self, axiom: &ProductionString, iterations: usize) -> HashMap<Symbol, u64> {
+        let mut counts = vec![0_u64; self.symbols.len()];
+        let mut result: HashMap<Symbol, u64> = HashMap::new();
+
+        for symbol in axiom {
+            match self.indices.get(&symbol) {
+                Some(i) => counts[*i] = counts[*i].saturating_add(1),
+                // Not part of any production, so it is never rewritten.
+                None => *result.entry(symbol).or_default() += 1
+            }
+        }
+
+        let counts = multiply_vector(&counts, &self.power(iterations));
+
+        for (i, count) in counts.into_iter().enumerate() {
+            if count > 0 {
+                result.insert(self.symbols[i], count);
+            }
+        }
+
+        result
+    }
+
+    /// Predicts the length of the string produced by deriving `axiom` for the given number
+    /// of iterations.
+    pub fn length(&self, axiom: &ProductionString, iterations: usize) -> u64 {
+        self.symbol_counts(axiom, iterations)
+            .values()
+            .fold(0_u64, |total, count| total.saturating_add(*count))
+    }
+
+    /// Estimates the dominant eigenvalue of the matrix using power iteration.
+    ///
+    /// This is the factor by which string lengths eventually grow with each iteration.
+    /// Values greater than one indicate exponential growth, a value of one indicates polynomial
+    /// (or no) growth, and zero indicates that strings eventually disappear.
+    ///
+    /// The ratio between the lengths of successive iterations need not converge: for a periodic
+    /// system, such as `A -> B B` and `B -> A`, it alternates. The estimate is therefore the
+    /// geometric mean of the ratios over the last half of the iterations, which converges for
+    /// every system.
+    pub fn growth_rate(&self) -> f64 {
+        const ITERATIONS: usize = 1000;
+        const AVERAGED: usize = ITERATIONS / 2;
+
+        let size = self.symbols.len();
+        if size == 0 {
+            return 1.0;
+        }
+
+        let mut vector = vec![1.0_f64 / size as f64; size];
+        let mut log_rates = 0.0_f64;
+
+        for iteration in 0..ITERATIONS {
+            let mut next = vec![0.0_f64; size];
+            for (i, value) in vector.iter().enumerate() {
+                for (j, entry) in self.matrix[i].iter().enumerate() {
+                    next[j] += value * *entry as f64;
+                }
+            }
+
+            let rate: f64 = next.iter().sum();
+            if rate == 0.0 {
+                return 0.0;
+            }
+
+            if iteration >= ITERATIONS - AVERAGED {
+                log_rates += rate.ln();
+            }
+
+            next.iter_mut().for_each(|v| *v /= rate);
+            vector = next;
+        }
+
+        (log_rates / AVERAGED as f64).exp()
+    }
+
+    /// Returns the matrix raised to the given power, using repeated squaring.
+    fn power(&self, mut exponent: usize) -> Vec<Vec<u64>> {
+        let size = self.symbols.len();
+        let mut result: Vec<Vec<u64>> = (0..size)
+            .map(|i| (0..size).map(|j| u64::from(i == j)).collect())
+            .collect();
+        let mut base = self.matrix.clone();
+
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = multiply(&result, &base);
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = multiply(&base, &base);
+            }
+        }
+
+        result
+    }
+}
+
+fn multiply(a: &[Vec<u64>], b: &[Vec<u64>]) -> Vec<Vec<u64>> {
+    a.iter().map(|row| multiply_vector(row, b)).collect()
+}
+
+fn multiply_vector(vector: &[u64], matrix: &[Vec<u64>]) -> Vec<u64> {
+    let mut result = vec![0_u64; vector.len()];
+    for (i, value) in vector.iter().enumerate() {
+        if *value == 0 {
+            continue;
+        }
+
+        for (j, entry) in matrix[i].iter().enumerate() {
+            result[j] = result[j].saturating_add(value.saturating_mul(*entry));
+        }
+    }
+
+    result
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_prod_string;
+
+    #[test]
+    fn predicts_lengths() {
+        let system = System::new();
+        system.add_production("X -> Forward + [ [ X ] - X ] - Forward [ - Forward X ] + X").unwrap();
+        system.add_production("Forward -> Forward Forward").unwrap();
+
+        let growth = system.growth_matrix().unwrap();
+        let axiom = parse_prod_string("X").unwrap();
+
+        for iterations in 0..6 {
+            let derived = system.derive(axiom.clone(), RunSettings::for_max_iterations(iterations)).unwrap();
+            assert_eq!(growth.length(&axiom, iterations), derived.len() as u64);
+
+            let counts = growth.symbol_counts(&axiom, iterations);
+            let forward = Symbol::build("Forward").unwrap();
+            let expected = derived.iter().filter(|s| **s == forward).count() as u64;
+            assert_eq!(counts.get(&forward).copied().unwrap_or(0), expected);
+        }
+    }
+
+    #[test]
+    fn saturates() {
+        let system = System::new();
+        system.add_production("F -> F F").unwrap();
+
+        let growth = system.growth_matrix().unwrap();
+        let axiom = parse_prod_string("F").unwrap();
+        assert_eq!(growth.length(&axiom, 10), 1024);
+        assert_eq!(growth.length(&axiom, 200), u64::MAX);
+        assert!((growth.growth_rate() - 2.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn unknown_symbols_are_constant() {
+        let system = System::new();
+        system.add_production("A -> A A").unwrap();
+
+        let growth = system.growth_matrix().unwrap();
+        let axiom = parse_prod_string("Q A Q").unwrap();
+        assert_eq!(growth.length(&axiom, 3), 10);
+    }
+
+    #[test]
+    fn polynomial_and_vanishing_growth() {
+        let system = System::new();
+        system.add_production("A -> A B").unwrap();
+        assert!((system.growth_matrix().unwrap().growth_rate() - 1.0).abs() < 0.01);
+
+        let system = System::new();
+        system.add_production("A -> ").unwrap();
+        assert_eq!(system.growth_matrix().unwrap().growth_rate(), 0.0);
+    }
+
+    #[test]
+    fn periodic_growth() {
+        let system = System::new();
+        system.add_production("A -> B B").unwrap();
+        system.add_production("B -> A").unwrap();
+        assert!((system.growth_matrix().unwrap().growth_rate() - 2.0_f64.sqrt()).abs() < 0.001);
+
+        let system = System::new();
+        system.add_production("A -> B").unwrap();
+        system.add_production("B -> C").unwrap();
+        system.add_production("C -> A A").unwrap();
+        assert!((system.growth_matrix().unwrap().growth_rate() - 2.0_f64.cbrt()).abs() < 0.001);
+    }
+
+    #[test]
+    fn rejects_non_d0l_systems() {
+        let system = System::new();
+        system.add_production("A < B -> B").unwrap();
+        assert!(system.growth_matrix().is_err());
+
+        let system = System::new();
+        system.add_production("A -> 0.5 B").unwrap();
+        system.add_production("A -> 0.5 C").unwrap();
+        assert!(system.growth_matrix().is_err());
+    }
+
+    #[test]
+    fn derive_respects_max_length() {
+        let system = System::new();
+        system.add_production("F -> F F").unwrap();
+        let axiom = parse_prod_string("F").unwrap();
+
+        let settings = RunSettings { max_length: Some(100), ..RunSettings::for_max_iterations(6) };
+        assert_eq!(system.derive(axiom.clone(), settings).unwrap().len(), 64);
+
+        let settings = RunSettings { max_length: Some(100), ..RunSettings::for_max_iterations(7) };
+        assert!(system.derive(axiom.clone(), settings).is_err());
+
+        // Not D0L, so this is checked while deriving instead.
+        system.add_production("G < F -> G").unwrap();
+        let settings = RunSettings { max_length: Some(100), ..RunSettings::for_max_iterations(7) };
+        assert!(system.derive(axiom, settings).is_err());
+    }
+}
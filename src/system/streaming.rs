@@ -0,0 +1,258 @@
+//! Deriving strings too long to hold in memory.
+//!
+//! [`System::derive`] produces every generation of a derivation in full, and so the memory it
+//! needs grows with the length of the strings it produces. For context-free systems, each
+//! symbol of the final string can instead be found by expanding the axiom depth first: the
+//! first symbol of the axiom is rewritten, then the first symbol of what it produced, and so
+//! on until the last generation is reached. [`StreamingDerivation`] does this lazily, and only
+//! needs memory for one production body per generation.
+//!
+//! ```
+//! use rusty_systems::prelude::*;
+//!
+//! let system = System::new();
+//! system.add_production("A -> A B").unwrap();
+//! system.add_production("B -> A").unwrap();
+//!
+//! let axiom: ProductionString = "A".parse().unwrap();
+//! let stream = system.derive_streaming(axiom.clone(), RunSettings::for_max_iterations(5)).unwrap();
+//! let string = system.derive(axiom, RunSettings::for_max_iterations(5)).unwrap();
+//!
+//! assert_eq!(stream.collect::<ProductionString>(), string);
+//! ```
+//!
+//! The symbols can be given to an interpretation without ever collecting them into a string.
+//! See [`Interpretation::interpret_symbols`].
+//!
+//! Productions may be stochastic, and the system may [cut branches](System::with_cut_symbol),
+//! but productions with contexts, or with a sequence of symbols as their head, need to see
+//! the rest of the string and so cannot be streamed.
+
+use std::collections::HashMap;
+use std::ops::Deref;
+
+use rand::rngs::StdRng;
+
+use crate::error::Error;
+use crate::prelude::*;
+use crate::productions::Production;
+use crate::Result;
+use crate::strings::Cutter;
+use super::RewriteMode;
+
+/// An iterator over the symbols of the last generation of a derivation, produced depth first.
+/// See the [module documentation](crate::system::streaming).
+///
+/// This is created using [`System::derive_streaming`].
+#[derive(Debug, Clone)]
+pub struct StreamingDerivation {
+    productions: Vec<Production>,
+    /// The production to apply to each symbol that has one.
+    rules: HashMap<Symbol, usize>,
+    axiom: ProductionString,
+    /// The index of the next symbol of the axiom.
+    position: usize,
+    /// What remains to be produced at each generation after the axiom.
+    frames: Vec<Frame>,
+    /// For each generation after the axiom, what the system's cut symbol removes, if it has one.
+    cutters: Vec<Option<Cutter>>,
+    /// Chooses the bodies of stochastic productions. See [`RunSettings::seed`].
+    rng: StdRng
+}
+
+/// The symbols that a generation is producing from a single symbol of the generation before.
+#[derive(Debug, Copy, Clone)]
+enum Frame {
+    /// A symbol without a production, which is copied unchanged.
+    Unchanged(Option<Symbol>),
+    /// The symbols of a production body, from `position` onwards.
+    Body { production: usize, body: usize, position: usize }
+}
+
+impl StreamingDerivation {
+    /// Starts a derivation of the axiom, using a copy of the system's productions.
+    ///
+    /// The derivation runs for [`RunSettings::max_iterations`] generations. As the string
+    /// is never held in memory, [`RunSettings::max_length`] is not enforced.
+    ///
+    /// This returns an [`ErrorKind::Definitions`](crate::error::ErrorKind::Definitions) error
+    /// if a production has a context or a sequence as its head, or if its bodies do not have valid
+    /// chances, and an [`ErrorKind::Execution`](crate::error::ErrorKind::Execution) error if
+    /// the settings do not rewrite symbols in [parallel](RewriteMode::Parallel).
+    pub fn new(system: &System, axiom: ProductionString, settings: RunSettings) -> Result<Self> {
+        if settings.rewrite_mode != RewriteMode::Parallel {
+            return Err(Error::execution("streaming derivations can only rewrite symbols in parallel"));
+        }
+
+        let productions = system.productions.read()?.deref().clone();
+        let mut rules: HashMap<Symbol, usize> = HashMap::new();
+
+        for (index, production) in productions.iter().enumerate() {
+            let head = production.head();
+            let has_context = head.pre_context().is_some_and(|c| !c.is_empty()) ||
+                head.post_context().is_some_and(|c| !c.is_empty());
+
+            if has_context || head.is_sequence() {
+                return Err(Error::definition(
+                    format!("the production [{head}] is not context-free, and so cannot be streamed")));
+            }
+
+            // Choosing a body only fails if the chances of the bodies are invalid.
+            production.choose_body_index().map_err(|e| Error::definition(format!("the production [{head}]: {e}")))?;
+
+            match rules.get(head.target()) {
+                Some(current) if !production.precedence_cmp(&productions[*current]).is_gt() => {}
+                _ => { rules.insert(*head.target(), index); }
+            }
+        }
+
+        Ok(StreamingDerivation {
+            productions,
+            rules,
+            axiom,
+            position: 0,
+            frames: vec![Frame::Unchanged(None); settings.max_iterations],
+            cutters: vec![system.cut.map(|cut| cut.cutter()); settings.max_iterations],
+            rng: settings.rng()
+        })
+    }
+
+    /// The number of generations that the axiom is rewritten for.
+    #[inline]
+    pub fn generations(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// The next symbol of the given generation, where the axiom is generation zero.
+    fn next_at(&mut self, generation: usize) -> Option<Symbol> {
+        if generation == 0 {
+            let symbol = self.axiom.symbols().get(self.position).copied();
+            self.position += 1;
+            return symbol;
+        }
+
+        loop {
+            let symbol = match self.take(generation - 1) {
+                Some(symbol) => symbol,
+                None => {
+                    let parent = self.next_at(generation - 1)?;
+                    self.frames[generation - 1] = self.expand(parent);
+                    continue;
+                }
+            };
+
+            let kept = match &mut self.cutters[generation - 1] {
+                Some(cutter) => cutter.keeps(symbol),
+                None => true
+            };
+
+            if kept {
+                return Some(symbol);
+            }
+        }
+    }
+
+    /// Takes the next symbol from a frame, if it has any left.
+    fn take(&mut self, frame: usize) -> Option<Symbol> {
+        match &mut self.frames[frame] {
+            Frame::Unchanged(symbol) => symbol.take(),
+            Frame::Body { production, body, position } => {
+                let symbol = self.productions[*production].all_bodies()[*body].string().symbols().get(*position).copied();
+                *position += 1;
+                symbol
+            }
+        }
+    }
+
+    /// The frame producing whatever `symbol` is rewritten to.
+    fn expand(&mut self, symbol: Symbol) -> Frame {
+        let production = match self.rules.get(&symbol) {
+            None => return Frame::Unchanged(Some(symbol)),
+            Some(production) => *production
+        };
+
+        // Bodies were checked when the derivation was created, and so can always be chosen.
+        let body = self.productions[production].choose_body_index_with(&mut self.rng)
+            .expect("bodies are checked when the derivation is created");
+
+        Frame::Body { production, body, position: 0 }
+    }
+}
+
+impl Iterator for StreamingDerivation {
+    type Item = Symbol;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_at(self.frames.len())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::parser::parse_prod_string;
+
+    fn streamed(system: &System, axiom: &str, iterations: usize) -> ProductionString {
+        system.derive_streaming(parse_prod_string(axiom).unwrap(), RunSettings::for_max_iterations(iterations))
+            .unwrap()
+            .collect()
+    }
+
+    fn derived(system: &System, axiom: &str, iterations: usize) -> ProductionString {
+        system.derive(parse_prod_string(axiom).unwrap(), RunSettings::for_max_iterations(iterations)).unwrap()
+    }
+
+    #[test]
+    fn matches_derive() {
+        let system = System::new();
+        system.add_production("X -> F [ + X ] F [ - X ] + X").unwrap();
+        system.add_production("F -> F F").unwrap();
+        system.add_production("E -> ").unwrap();
+
+        for iterations in 0..5 {
+            assert_eq!(streamed(&system, "X E G", iterations), derived(&system, "X E G", iterations));
+        }
+
+        assert!(streamed(&system, "E", 3).is_empty());
+    }
+
+    #[test]
+    fn matches_derive_with_cuts() {
+        let system = System::new().with_cut_symbol(
+            Symbol::build("%").unwrap(), Symbol::build("[").unwrap(), Symbol::build("]").unwrap());
+        system.add_production("A -> F [ B ] A [ F A ] C").unwrap();
+        system.add_production("B -> F [ A ] %").unwrap();
+        system.add_production("C -> % F").unwrap();
+
+        for iterations in 0..6 {
+            assert_eq!(streamed(&system, "A [ C ] F", iterations), derived(&system, "A [ C ] F", iterations));
+        }
+    }
+
+    #[test]
+    fn streams_stochastic_productions() {
+        let system = System::new();
+        system.add_production("A -> B").unwrap();
+        system.add_production("A -> C").unwrap();
+
+        let string = streamed(&system, "A A A A", 1);
+        assert_eq!(string.len(), 4);
+        assert!(string.iter().all(|s| s.to_string() == "B" || s.to_string() == "C"));
+    }
+
+    #[test]
+    fn refuses_context_sensitive_systems() {
+        let system = System::new();
+        system.add_production("A < B -> C").unwrap();
+        let error = system.derive_streaming(parse_prod_string("A B").unwrap(), RunSettings::default()).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::Definitions));
+
+        let system = System::new();
+        system.add_production("A -> B").unwrap();
+        let settings = RunSettings { rewrite_mode: RewriteMode::LeftmostSequential, ..RunSettings::default() };
+        let error = system.derive_streaming(parse_prod_string("A").unwrap(), settings).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::Execution));
+    }
+}
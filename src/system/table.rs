@@ -0,0 +1,90 @@
+//! Finding the production that rewrites a symbol without searching every production.
+//!
+//! [`find_matching`](crate::system::find_matching) checks each production in turn, for every
+//! symbol of a string. A [`ProductionTable`] instead groups productions by the symbol that
+//! they rewrite, ordered by [precedence](Production::precedence_cmp), so that only the productions
+//! that could rewrite a symbol are checked, and the first that matches is the one to apply.
+//!
+//! ```
+//! use rusty_systems::prelude::*;
+//! use rusty_systems::productions::Production;
+//! use rusty_systems::system::table::ProductionTable;
+//!
+//! let productions: Vec<Production> = vec!["S -> S S".parse().unwrap(), "G < S -> G".parse().unwrap()];
+//! let table = ProductionTable::new(&productions);
+//!
+//! let string = "G S".parse().unwrap();
+//! assert_eq!(table.find(&string, 1).unwrap().head().to_string(), "G < S");
+//! assert!(table.find(&string, 0).is_none());
+//! ```
+//!
+//! The derivation functions of [`System`] build a table once for each derivation.
+
+use std::collections::HashMap;
+
+use crate::prelude::*;
+use crate::productions::Production;
+
+/// Productions indexed by the code of the symbol that they rewrite. See the
+/// [module documentation](crate::system::table).
+#[derive(Debug, Clone, Default)]
+pub struct ProductionTable<'a> {
+    /// For each target, its productions from the highest precedence to the lowest.
+    targets: HashMap<u32, Vec<&'a Production>>
+}
+
+impl<'a> ProductionTable<'a> {
+    /// Indexes the productions. Productions of equal precedence keep the order they are
+    /// given in, as they do for [`find_matching`](crate::system::find_matching).
+    pub fn new(productions: &'a [Production]) -> Self {
+        let mut targets: HashMap<u32, Vec<&'a Production>> = HashMap::new();
+        for production in productions {
+            targets.entry(production.head().target().code()).or_default().push(production);
+        }
+
+        // Sorting is stable, and so ties are still broken by order.
+        targets.values_mut().for_each(|candidates| candidates.sort_by(|a, b| b.precedence_cmp(a)));
+        ProductionTable { targets }
+    }
+
+    /// The productions whose target is the given symbol, from the highest precedence to the lowest.
+    pub fn candidates(&self, symbol: Symbol) -> &[&'a Production] {
+        self.targets.get(&symbol.code()).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Returns the production that rewrites the string at the given index, if any.
+    /// This is the production that [`find_matching`](crate::system::find_matching) would return.
+    pub fn find(&self, string: &ProductionString, index: usize) -> Option<&'a Production> {
+        let symbol = *string.symbols().get(index)?;
+        self.candidates(symbol).iter()
+            .find(|production| production.matches(string, index))
+            .copied()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_prod_string;
+    use crate::system::find_matching;
+
+    #[test]
+    fn agrees_with_find_matching() {
+        let productions: Vec<Production> = [
+            "A -> B", "B < A -> C", "A > B -> D", "B < A > B -> E", "B -> A", "C A -> F"
+        ].map(|p| p.parse().unwrap()).to_vec();
+        let productions = [productions, vec!["A -> G".parse::<Production>().unwrap().with_priority(-1)]].concat();
+
+        let table = ProductionTable::new(&productions);
+        let string = parse_prod_string("A B A B C A A X").unwrap();
+
+        for index in 0..=string.len() {
+            let expected = find_matching(&productions, &string, index).map(|p| p.head().to_string());
+            assert_eq!(table.find(&string, index).map(|p| p.head().to_string()), expected, "index {index}");
+        }
+
+        assert_eq!(table.candidates(Symbol::build("A").unwrap()).len(), 5);
+        assert!(table.candidates(Symbol::build("X").unwrap()).is_empty());
+    }
+}
@@ -0,0 +1,334 @@
+//! Continuous growth using *timed* L-Systems.
+//!
+//! Rather than rewriting every symbol at once in discrete generations, each symbol in a
+//! timed system has an age, which increases as time passes. A symbol is rewritten once it
+//! reaches its *lifetime*, and the symbols that replace it start with whatever time was left over.
+//! This follows the timed DOL-systems of chapter 6 of [the Algorithmic Beauty of Plants][abop],
+//! and makes smooth animations of a plant's development possible.
+//!
+//! Lifetimes are given per symbol. Symbols without a lifetime are never rewritten, and symbols
+//! without a matching production simply continue to age.
+//!
+//! ```
+//! use rusty_systems::prelude::*;
+//!
+//! let system = System::new();
+//! system.add_production("A -> Forward A").unwrap();
+//!
+//! let mut growth = system.timed("A".parse().unwrap(), RunSettings::default())
+//!     .with_lifetime("A".parse().unwrap(), 1.0)
+//!     .with_lifetime("Forward".parse().unwrap(), 2.0);
+//!
+//! growth.advance(0.5).unwrap();
+//! assert_eq!(growth.string().len(), 1);
+//!
+//! growth.advance(1.0).unwrap();
+//! assert_eq!(growth.string().to_production_string().to_string(), "Forward A");
+//!
+//! // The new Forward is a quarter of the way through its lifetime.
+//! assert_eq!(growth.string().modules()[0].growth(), 0.25);
+//! ```
+//!
+//! Interpretations can use [`TimedModule::growth`] to draw partially grown symbols.
+//! See [`AbopTurtleInterpretation::interpret_timed`](crate::interpretation::abop::AbopTurtleInterpretation::interpret_timed).
+//!
+//! [abop]: http://algorithmicbotany.org/papers/#abop
+
+use std::collections::HashMap;
+use std::ops::Deref;
+
+use rand::rngs::StdRng;
+
+use crate::error::Error;
+use crate::prelude::*;
+use crate::Result;
+use super::table::ProductionTable;
+use crate::strings::cut_items;
+use super::Cut;
+
+/// A symbol, along with how long it has been in the string.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TimedModule {
+    pub symbol: Symbol,
+    /// The time since the symbol was produced.
+    pub age: f64,
+    /// The age at which the symbol is rewritten. Symbols without a lifetime are never rewritten.
+    pub lifetime: Option<f64>
+}
+
+impl TimedModule {
+    /// How far through its lifetime the symbol is, from zero when it is produced, to one
+    /// when it is rewritten. Symbols without a lifetime are always fully grown.
+    pub fn growth(&self) -> f64 {
+        match self.lifetime {
+            None => 1.0,
+            Some(lifetime) => (self.age / lifetime).clamp(0.0, 1.0)
+        }
+    }
+
+    /// Returns true iff the symbol has reached the end of its lifetime.
+    #[inline]
+    pub fn is_mature(&self) -> bool {
+        self.lifetime.is_some_and(|lifetime| self.age >= lifetime)
+    }
+}
+
+/// A string of [`TimedModule`] instances.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TimedString {
+    modules: Vec<TimedModule>
+}
+
+impl TimedString {
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.modules.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.modules.is_empty()
+    }
+
+    #[inline]
+    pub fn modules(&self) -> &[TimedModule] {
+        &self.modules
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, TimedModule> {
+        self.modules.iter()
+    }
+
+    /// Returns the symbols of the string, without their ages.
+    pub fn to_production_string(&self) -> ProductionString {
+        ProductionString::from(self.modules.iter().map(|m| m.symbol).collect::<Vec<_>>())
+    }
+}
+
+/// A derivation that advances through time. See the [module documentation](crate::system::timed).
+///
+/// This is created using [`System::timed`].
+#[derive(Debug, Clone)]
+pub struct TimedDerivation<'a> {
+    system: &'a System,
+    settings: RunSettings,
+    lifetimes: HashMap<Symbol, f64>,
+    time: f64,
+    string: TimedString,
+    /// Chooses the bodies of stochastic productions. See [`RunSettings::seed`].
+    rng: StdRng
+}
+
+impl<'a> TimedDerivation<'a> {
+    /// Starts a derivation of the given axiom at time zero, with every symbol aged zero.
+    ///
+    /// Each call to [`TimedDerivation::advance`] may rewrite the string at most
+    /// [`RunSettings::max_iterations`] times, and the string may not grow longer
+    /// than [`RunSettings::max_length`].
+    pub fn new(system: &'a System, axiom: ProductionString, settings: RunSettings) -> Self {
+        let modules = axiom.into_iter()
+            .map(|symbol| TimedModule { symbol, age: 0.0, lifetime: None })
+            .collect();
+
+        TimedDerivation {
+            system,
+            rng: settings.rng(),
+            settings,
+            lifetimes: HashMap::new(),
+            time: 0.0,
+            string: TimedString { modules }
+        }
+    }
+
+    /// Returns this derivation with the given lifetime for a symbol. Lifetimes must be positive.
+    pub fn with_lifetime(mut self, symbol: Symbol, lifetime: f64) -> Self {
+        self.lifetimes.insert(symbol, lifetime);
+        self.string.modules.iter_mut()
+            .filter(|m| m.symbol == symbol)
+            .for_each(|m| m.lifetime = Some(lifetime));
+        self
+    }
+
+    /// The lifetime of the given symbol, if it has one.
+    pub fn lifetime(&self, symbol: Symbol) -> Option<f64> {
+        self.lifetimes.get(&symbol).copied()
+    }
+
+    /// The total time that the derivation has been advanced by.
+    #[inline]
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    /// The string as it currently is.
+    #[inline]
+    pub fn string(&self) -> &TimedString {
+        &self.string
+    }
+
+    /// Ages every symbol by `dt`, rewriting those that reach the end of their lifetime.
+    /// If this returns an error, the derivation is left unchanged.
+    ///
+    /// Symbols produced by a rewrite start with the time that their predecessor had
+    /// left over, and so may themselves be rewritten during the same call.
+    /// Returns an [`ErrorKind::Execution`](crate::error::ErrorKind::Execution) error if this needs
+    /// more than [`RunSettings::max_iterations`] rewriting steps, or produces a string longer than
+    /// [`RunSettings::max_length`].
+    pub fn advance(&mut self, dt: f64) -> Result<()> {
+        if !dt.is_finite() || dt < 0.0 {
+            return Err(Error::execution(format!("time can only advance by a positive amount, not {dt}")));
+        }
+
+        if let Some((symbol, lifetime)) = self.lifetimes.iter().find(|(_, l)| l.is_nan() || **l <= 0.0) {
+            return Err(Error::definition(format!("the lifetime of [{symbol}] must be positive, not {lifetime}")));
+        }
+
+        let mut string = self.string.clone();
+        string.modules.iter_mut().for_each(|m| m.age += dt);
+
+        let productions = self.system.productions.read()?;
+        let table = ProductionTable::new(productions.deref());
+        let mut rng = self.rng.clone();
+        let mut steps = 0;
+
+        while let Some(next) = self.rewrite(&string, &table, &mut rng)? {
+            steps += 1;
+            if steps > self.settings.max_iterations {
+                return Err(Error::execution(
+                    format!("advancing by {dt} needs more than {} rewriting steps", self.settings.max_iterations)));
+            }
+
+            let next = apply_cut(next, self.system.cut.as_ref());
+            if let Some(max_length) = self.settings.max_length {
+                if next.len() > max_length {
+                    return Err(Error::execution(
+                        format!("derivation produced a string longer than the limit of {max_length}")));
+                }
+            }
+
+            string = next;
+        }
+
+        self.string = string;
+        self.rng = rng;
+        self.time += dt;
+        Ok(())
+    }
+
+    /// Rewrites every mature symbol that has a matching production. Returns [`None`] if there
+    /// were no such symbols.
+    ///
+    /// A production whose head is a sequence of symbols is applied when the first symbol of the
+    /// sequence is mature, and replaces the whole sequence.
+    fn rewrite(&self, current: &TimedString, table: &ProductionTable, rng: &mut StdRng) -> Result<Option<TimedString>> {
+        if !current.iter().any(TimedModule::is_mature) {
+            return Ok(None);
+        }
+
+        let string = current.to_production_string();
+        let mut modules = Vec::with_capacity(current.len());
+        let mut rewritten = false;
+
+        let mut index = 0;
+        while let Some(module) = current.modules.get(index) {
+            let production = match module.lifetime {
+                Some(lifetime) if module.age >= lifetime => table.find(&string, index)
+                    .map(|p| (p, module.age - lifetime)),
+                _ => None
+            };
+
+            match production {
+                None => {
+                    modules.push(*module);
+                    index += 1;
+                }
+                Some((production, age)) => {
+                    rewritten = true;
+                    for symbol in production.body_with(rng)?.string() {
+                        modules.push(TimedModule { symbol, age, lifetime: self.lifetime(symbol) });
+                    }
+                    index += production.head().predecessor_len();
+                }
+            }
+        }
+
+        Ok(rewritten.then_some(TimedString { modules }))
+    }
+}
+
+/// As [`ProductionString::apply_cut`], but keeping the age of each remaining symbol.
+fn apply_cut(string: TimedString, cut: Option<&Cut>) -> TimedString {
+    match cut {
+        Some(cut) if string.iter().any(|m| m.symbol == cut.cut) =>
+            TimedString { modules: cut_items(string.modules, cut.cutter(), |m| m.symbol) },
+        _ => string
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_prod_string;
+
+    fn symbol(name: &str) -> Symbol {
+        Symbol::build(name).unwrap()
+    }
+
+    #[test]
+    fn carries_left_over_time() {
+        let system = System::new();
+        system.add_production("A -> B A").unwrap();
+
+        let mut growth = system.timed(parse_prod_string("A").unwrap(), RunSettings::default())
+            .with_lifetime(symbol("A"), 1.0);
+
+        growth.advance(2.5).unwrap();
+        assert_eq!(growth.time(), 2.5);
+        assert_eq!(growth.string().to_production_string(), parse_prod_string("B B A").unwrap());
+
+        let ages: Vec<f64> = growth.string().iter().map(|m| m.age).collect();
+        assert_eq!(ages, [1.5, 0.5, 0.5]);
+
+        // B has no lifetime, and so is always fully grown.
+        assert_eq!(growth.string().modules()[0].growth(), 1.0);
+        assert_eq!(growth.string().modules()[2].growth(), 0.5);
+    }
+
+    #[test]
+    fn small_steps_match_large_ones() {
+        let system = System::new();
+        system.add_production("A -> B [ A ] A").unwrap();
+        system.add_production("B -> B B").unwrap();
+
+        let start = || system.timed(parse_prod_string("A").unwrap(), RunSettings::default())
+            .with_lifetime(symbol("A"), 1.0)
+            .with_lifetime(symbol("B"), 1.5);
+
+        let mut large = start();
+        large.advance(4.0).unwrap();
+
+        let mut small = start();
+        for _ in 0..16 {
+            small.advance(0.25).unwrap();
+        }
+
+        assert_eq!(large.string(), small.string());
+    }
+
+    #[test]
+    fn limits_steps_and_lifetimes() {
+        let system = System::new();
+        system.add_production("A -> A").unwrap();
+
+        let mut growth = system.timed(parse_prod_string("A").unwrap(), RunSettings::for_max_iterations(3))
+            .with_lifetime(symbol("A"), 1.0);
+        assert!(growth.advance(3.5).is_ok());
+        assert!(growth.advance(4.0).is_err());
+        assert_eq!(growth.time(), 3.5);
+        assert!(growth.advance(-1.0).is_err());
+
+        let mut growth = system.timed(parse_prod_string("A").unwrap(), RunSettings::default())
+            .with_lifetime(symbol("A"), 0.0);
+        assert!(growth.advance(1.0).is_err());
+    }
+}